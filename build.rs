@@ -1,8 +1,32 @@
-use std::path::Path;
+use std::{env, fs, path::Path};
 extern crate rustsourcebundler;
 use rustsourcebundler::Bundler;
 
+#[allow(dead_code)]
+#[path = "src/params.rs"]
+mod params;
+
+const DEFAULT_PARAMS: &str = "params/default.params";
+
+fn bake_params() -> Result<(), Box<dyn std::error::Error>> {
+    let path = env::var("CGBOT_PARAMS").unwrap_or_else(|_| DEFAULT_PARAMS.to_string());
+    let params = params::StrategyParams::load(Path::new(&path))?;
+    let source = params.to_baked_source(&path);
+
+    let out = Path::new("src/baked_params.rs");
+    if fs::read_to_string(out).ok().as_deref() != Some(source.as_str()) {
+        fs::write(out, source)?;
+    }
+    Ok(())
+}
+
 fn main() -> Result<(), Box<dyn std::error::Error>> {
+    println!("cargo:rerun-if-env-changed=CGBOT_PARAMS");
+    println!("cargo:rerun-if-changed=params");
+    println!("cargo:rerun-if-changed=src");
+
+    bake_params()?;
+
     let mut bundler: Bundler =
         Bundler::new(Path::new("src/bin/main.rs"), Path::new("src/bin/merged.rs"));
     bundler.crate_name("cgbot");
@@ -0,0 +1,250 @@
+use std::collections::HashMap;
+
+use super::*;
+
+/// Minimum cost assignment of every row to a distinct column by the
+/// Hungarian algorithm, needs no more rows than columns.
+fn hungarian(cost: &[Vec<f32>]) -> Vec<usize> {
+    let (n, m) = (cost.len(), cost.first().map_or(0, Vec::len));
+
+    // Potentials and matching are 1-indexed, column 0 is the virtual root.
+    let mut u = vec![0.; n + 1];
+    let mut v = vec![0.; m + 1];
+    let mut row_of = vec![0; m + 1];
+    let mut way = vec![0; m + 1];
+
+    for row in 1..=n {
+        row_of[0] = row;
+        let mut col = 0;
+        let mut min_to = vec![f32::INFINITY; m + 1];
+        let mut used = vec![false; m + 1];
+
+        loop {
+            used[col] = true;
+            let r = row_of[col];
+            let (mut delta, mut next) = (f32::INFINITY, 0);
+
+            for c in 1..=m {
+                if used[c] {
+                    continue;
+                }
+
+                let reduced = cost[r - 1][c - 1] - u[r] - v[c];
+                if reduced < min_to[c] {
+                    min_to[c] = reduced;
+                    way[c] = col;
+                }
+                if min_to[c] < delta {
+                    delta = min_to[c];
+                    next = c;
+                }
+            }
+
+            for c in 0..=m {
+                if used[c] {
+                    u[row_of[c]] += delta;
+                    v[c] -= delta;
+                } else {
+                    min_to[c] -= delta;
+                }
+            }
+
+            col = next;
+            if row_of[col] == 0 {
+                break;
+            }
+        }
+
+        while col != 0 {
+            let prev = way[col];
+            row_of[col] = row_of[prev];
+            col = prev;
+        }
+    }
+
+    let mut assignment = vec![0; n];
+    for c in 1..=m {
+        if row_of[c] != 0 {
+            assignment[row_of[c] - 1] = c - 1;
+        }
+    }
+    assignment
+}
+
+/// Splits the fish left to scan between our drones, so that they don't
+/// chase the same region.
+#[derive(Default)]
+pub struct TaskAllocation {
+    /// Plan slot of the drone each fish is assigned to.
+    owners: HashMap<i32, usize>,
+}
+
+impl TaskAllocation {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Assigns every fish to one drone, minimizing the travel turns to the
+    /// centre of its bounds per point of value. Drones take at most an even
+    /// share of the fish each.
+    pub fn update(
+        &mut self,
+        world: &World,
+        bounds_detector: &BoundsDetector,
+        meta_strategy: &MetaStrategy,
+        drone_ids: &[i32],
+    ) {
+        self.owners.clear();
+
+        let drones = drone_ids
+            .iter()
+            .enumerate()
+            .filter_map(|(slot, id)| world.me.drones.get(id).map(|d| (slot, d)))
+            .filter(|(_, d)| d.emergency == 0)
+            .collect::<Vec<_>>();
+
+        let mut fish = world
+            .creatures
+            .values()
+            .filter(|c| is_unscanned(c, world))
+            .map(|c| c.id)
+            .collect::<Vec<_>>();
+        fish.sort();
+
+        if drones.is_empty() || fish.is_empty() {
+            return;
+        }
+
+        let capacity = fish.len().div_ceil(drones.len());
+
+        let cost = fish
+            .iter()
+            .map(|&id| {
+                let centre = bounds_detector.get_bounds(id).get_center();
+                let value = meta_strategy.get_fish_cost(id).max(f32::EPSILON);
+
+                (0..drones.len() * capacity)
+                    .map(|col| {
+                        let (_, drone) = drones[col % drones.len()];
+                        (centre - drone.pos).len() / DRONE_SPEED / value
+                    })
+                    .collect()
+            })
+            .collect::<Vec<Vec<f32>>>();
+
+        for (id, col) in fish.into_iter().zip(hungarian(&cost)) {
+            self.owners.insert(id, drones[col % drones.len()].0);
+        }
+    }
+
+    /// Plan slot of the drone the fish is assigned to.
+    pub fn owner(&self, fish_id: i32) -> Option<usize> {
+        self.owners.get(&fish_id).copied()
+    }
+
+    /// Score map weight of the fish for the drone in `drone_idx`: fish of the
+    /// other drones are discounted by `bias`.
+    pub fn weight(&self, fish_id: i32, drone_idx: usize, bias: f32) -> f32 {
+        match self.owner(fish_id) {
+            Some(owner) if owner != drone_idx => 1. - bias.clamp(0., 1.),
+            _ => 1.,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Costs in 0..100 from a fixed linear congruential sequence.
+    fn costs(n: usize, m: usize, seed: u64) -> Vec<Vec<f32>> {
+        let mut state = seed;
+        (0..n)
+            .map(|_| {
+                (0..m)
+                    .map(|_| {
+                        state = state
+                            .wrapping_mul(6364136223846793005)
+                            .wrapping_add(1442695040888963407);
+                        (state >> 33) as f32 % 100.
+                    })
+                    .collect()
+            })
+            .collect()
+    }
+
+    fn total(cost: &[Vec<f32>], assignment: &[usize]) -> f32 {
+        assignment
+            .iter()
+            .enumerate()
+            .map(|(r, &c)| cost[r][c])
+            .sum()
+    }
+
+    /// Cheapest assignment of the rows from `row` on to unused columns.
+    fn brute_force(cost: &[Vec<f32>], row: usize, used: &mut Vec<bool>) -> f32 {
+        if row == cost.len() {
+            return 0.;
+        }
+
+        let mut best = f32::INFINITY;
+        for col in 0..used.len() {
+            if !used[col] {
+                used[col] = true;
+                best = best.min(cost[row][col] + brute_force(cost, row + 1, used));
+                used[col] = false;
+            }
+        }
+        best
+    }
+
+    fn assert_optimal(cost: &[Vec<f32>]) -> Vec<usize> {
+        let assignment = hungarian(cost);
+
+        let mut cols = assignment.clone();
+        cols.sort();
+        cols.dedup();
+        assert_eq!(cols.len(), cost.len(), "columns reused: {assignment:?}");
+
+        let optimum = brute_force(cost, 0, &mut vec![false; cost[0].len()]);
+        assert_eq!(total(cost, &assignment), optimum);
+        assignment
+    }
+
+    #[test]
+    fn square_assignment() {
+        let cost = vec![vec![4., 1., 3.], vec![2., 0., 5.], vec![3., 2., 2.]];
+        assert_eq!(assert_optimal(&cost), vec![1, 0, 2]);
+
+        for seed in 0..20 {
+            assert_optimal(&costs(5, 5, seed));
+        }
+    }
+
+    #[test]
+    fn rectangular_assignment() {
+        for seed in 0..20 {
+            assert_optimal(&costs(3, 6, seed));
+        }
+    }
+
+    #[test]
+    fn duplicated_columns_cap_every_drone() {
+        let (fish, drones) = (5, 2);
+        let capacity = fish / drones + 1;
+
+        for seed in 0..20 {
+            let per_drone = costs(fish, drones, seed);
+            let cost = per_drone
+                .iter()
+                .map(|row| (0..drones * capacity).map(|c| row[c % drones]).collect())
+                .collect::<Vec<Vec<f32>>>();
+
+            let assignment = assert_optimal(&cost);
+            for drone in 0..drones {
+                let count = assignment.iter().filter(|&&c| c % drones == drone).count();
+                assert!(count <= capacity);
+            }
+        }
+    }
+}
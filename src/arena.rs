@@ -0,0 +1,701 @@
+use std::{
+    collections::{BTreeMap, HashMap, HashSet},
+    f32::consts::PI,
+};
+
+use rand::{rngs::StdRng, Rng, SeedableRng};
+
+use super::*;
+
+const MAX_TURNS: i32 = 200;
+const DRONE_SPEED: f32 = 600.;
+const EMERGENCY_SPEED: f32 = 300.;
+const SINK_SPEED: f32 = 300.;
+const LIGHT_RADIUS: f32 = 800.;
+const POWERED_LIGHT_RADIUS: f32 = 2000.;
+const MAX_BATTERY: i32 = 30;
+const LIGHT_COST: i32 = 5;
+const SURFACE: f32 = 500.;
+const FISH_SPEED: f32 = 200.;
+const FISH_FLEE_SPEED: f32 = 400.;
+const FISH_HEAR_RADIUS: f32 = 1400.;
+const MONSTER_SPEED: f32 = 270.;
+const MONSTER_ATTACK_SPEED: f32 = 540.;
+const MONSTER_KILL_RADIUS: f32 = 500.;
+const MONSTER_VISIBILITY_BONUS: f32 = 300.;
+const AVOID_RADIUS: f32 = 600.;
+
+pub trait Bot {
+    fn commands(&mut self, world: &World) -> Vec<DroneCommand>;
+}
+
+impl Bot for Strategy {
+    fn commands(&mut self, world: &World) -> Vec<DroneCommand> {
+        self.decide(world)
+    }
+}
+
+struct ArenaCreature {
+    id: i32,
+    color: i8,
+    typ: i8,
+    pos: Vec2,
+    vel: Vec2,
+    alive: bool,
+}
+
+impl ArenaCreature {
+    fn habitat(&self) -> (f32, f32) {
+        match self.typ {
+            -1 => (2500., 9999.),
+            t => (2500. * (t + 1) as f32, 2500. * (t + 2) as f32 - 1.),
+        }
+    }
+}
+
+struct ArenaDrone {
+    id: i32,
+    pos: Vec2,
+    bat: i32,
+    emergency: bool,
+    light: bool,
+    scans: HashSet<i32>,
+}
+
+impl ArenaDrone {
+    fn light_radius(&self) -> f32 {
+        if self.light {
+            POWERED_LIGHT_RADIUS
+        } else {
+            LIGHT_RADIUS
+        }
+    }
+}
+
+#[derive(Default)]
+struct ArenaPlayer {
+    score: i32,
+    saved: HashSet<i32>,
+    completed_types: HashSet<i8>,
+    completed_colors: HashSet<i8>,
+    drones: Vec<ArenaDrone>,
+}
+
+/// Offline referee for local games between two bots.
+pub struct Arena {
+    creatures: Vec<ArenaCreature>,
+    players: [ArenaPlayer; 2],
+    turn: i32,
+}
+
+fn mirror(pos: Vec2) -> Vec2 {
+    Vec2::new(9999. - pos.x, pos.y)
+}
+
+/// Checks whether two objects moving along straight segments during a turn
+/// come closer than `radius`.
+fn segments_collide(a0: Vec2, a1: Vec2, b0: Vec2, b1: Vec2, radius: f32) -> bool {
+    let p = a0 - b0;
+    let v = (a1 - a0) - (b1 - b0);
+    let vv = v.x * v.x + v.y * v.y;
+
+    let t = if vv > 0. {
+        (-(p.x * v.x + p.y * v.y) / vv).clamp(0., 1.)
+    } else {
+        0.
+    };
+
+    (p + v * t).len() < radius
+}
+
+fn blip_direction(from: Vec2, to: Vec2) -> BlipDirection {
+    match (to.x < from.x, to.y < from.y) {
+        (true, true) => BlipDirection::TL,
+        (true, false) => BlipDirection::BL,
+        (false, true) => BlipDirection::TR,
+        (false, false) => BlipDirection::BR,
+    }
+}
+
+impl Arena {
+    pub fn new(seed: u64) -> Self {
+        let mut rng = StdRng::seed_from_u64(seed);
+        let mut creatures = Vec::new();
+
+        // Colours come in mirrored pairs so that both players start in the
+        // same situation.
+        for pair in 0..2 {
+            for typ in 0..3 {
+                let (min_y, max_y) = (2500. * (typ + 1) as f32, 2500. * (typ + 2) as f32);
+                let pos = Vec2::new(rng.gen_range(0. ..5000.), rng.gen_range(min_y..max_y));
+                let vel = Vec2::new(1., 0.).rotate(rng.gen_range(-PI..PI)) * FISH_SPEED;
+
+                for (color, pos, vel) in [
+                    (pair * 2, pos, vel),
+                    (pair * 2 + 1, mirror(pos), Vec2::new(-vel.x, vel.y)),
+                ] {
+                    creatures.push(ArenaCreature {
+                        id: 4 + creatures.len() as i32,
+                        color,
+                        typ,
+                        pos,
+                        vel,
+                        alive: true,
+                    });
+                }
+            }
+        }
+
+        let monster_pairs = rng.gen_range(1..4);
+        for _ in 0..monster_pairs {
+            let pos = Vec2::new(rng.gen_range(0. ..5000.), rng.gen_range(5000. ..9999.));
+            let vel = Vec2::new(1., 0.).rotate(rng.gen_range(-PI..PI)) * MONSTER_SPEED;
+
+            for (pos, vel) in [(pos, vel), (mirror(pos), Vec2::new(-vel.x, vel.y))] {
+                creatures.push(ArenaCreature {
+                    id: 4 + creatures.len() as i32,
+                    color: -1,
+                    typ: -1,
+                    pos,
+                    vel,
+                    alive: true,
+                });
+            }
+        }
+
+        let mut players: [ArenaPlayer; 2] = Default::default();
+        for (i, x) in [2000., 7000.].into_iter().enumerate() {
+            for (p, player) in players.iter_mut().enumerate() {
+                let pos = Vec2::new(x, SURFACE);
+                player.drones.push(ArenaDrone {
+                    id: (i * 2 + p) as i32,
+                    pos: if p == 0 { pos } else { mirror(pos) },
+                    bat: MAX_BATTERY,
+                    emergency: false,
+                    light: false,
+                    scans: HashSet::new(),
+                });
+            }
+        }
+
+        Arena {
+            creatures,
+            players,
+            turn: 0,
+        }
+    }
+
+    pub fn scores(&self) -> [i32; 2] {
+        [self.players[0].score, self.players[1].score]
+    }
+
+    pub fn is_over(&self) -> bool {
+        if self.turn >= MAX_TURNS {
+            return true;
+        }
+
+        self.creatures
+            .iter()
+            .filter(|c| c.typ != -1 && c.alive)
+            .all(|c| self.players.iter().all(|p| p.saved.contains(&c.id)))
+    }
+
+    fn is_visible(&self, player: usize, creature: &ArenaCreature) -> bool {
+        let bonus = if creature.typ == -1 {
+            MONSTER_VISIBILITY_BONUS
+        } else {
+            0.
+        };
+
+        creature.alive
+            && self.players[player]
+                .drones
+                .iter()
+                .filter(|d| !d.emergency)
+                .any(|d| (d.pos - creature.pos).len() < d.light_radius() + bonus)
+    }
+
+    fn build_player(&self, player: usize, blips: bool) -> Player {
+        let p = &self.players[player];
+
+        let drones = p
+            .drones
+            .iter()
+            .map(|d| {
+                let blips = if blips {
+                    self.creatures
+                        .iter()
+                        .filter(|c| c.alive)
+                        .map(|c| (c.id, blip_direction(d.pos, c.pos)))
+                        .collect()
+                } else {
+                    HashMap::new()
+                };
+
+                let drone = Drone {
+                    id: d.id,
+                    pos: Vec2::new(d.pos.x.round(), d.pos.y.round()),
+                    bat: d.bat,
+                    emergency: d.emergency as i32,
+                    blips,
+                    scans: d.scans.clone(),
+                };
+                (d.id, drone)
+            })
+            .collect::<BTreeMap<_, _>>();
+
+        Player {
+            score: p.score,
+            scans: p.saved.clone(),
+            drones,
+        }
+    }
+
+    /// Builds the world as seen by `player` at the start of the current turn.
+    pub fn world_for(&self, player: usize) -> World {
+        let creatures = self
+            .creatures
+            .iter()
+            .map(|c| {
+                let visible = self.is_visible(player, c);
+                let creature = Creature::new(
+                    c.id,
+                    c.color,
+                    c.typ,
+                    visible.then(|| Vec2::new(c.pos.x.round(), c.pos.y.round())),
+                    visible.then(|| Vec2::new(c.vel.x.round(), c.vel.y.round())),
+                );
+                (c.id, creature)
+            })
+            .collect();
+
+        World {
+            creatures,
+            me: self.build_player(player, true),
+            opponent: self.build_player(1 - player, false),
+            iter: self.turn,
+        }
+    }
+
+    fn apply_commands(&mut self, player: usize, commands: &[DroneCommand]) {
+        for (drone, command) in self.players[player].drones.iter_mut().zip(commands) {
+            drone.light = false;
+
+            if drone.emergency {
+                continue;
+            }
+
+            if command.light && drone.bat >= LIGHT_COST {
+                drone.light = true;
+                drone.bat -= LIGHT_COST;
+            } else {
+                drone.bat = (drone.bat + 1).min(MAX_BATTERY);
+            }
+        }
+    }
+
+    fn drone_targets(&self, player: usize, commands: &[DroneCommand]) -> Vec<Vec2> {
+        self.players[player]
+            .drones
+            .iter()
+            .enumerate()
+            .map(|(i, drone)| {
+                let target = if drone.emergency {
+                    drone.pos - Vec2::new(0., EMERGENCY_SPEED)
+                } else if commands.get(i).is_some_and(|command| command.wait) {
+                    drone.pos + Vec2::new(0., SINK_SPEED)
+                } else if let Some(command) = commands.get(i) {
+                    let delta = command.target - drone.pos;
+                    if delta.len() > DRONE_SPEED {
+                        drone.pos + delta.norm() * DRONE_SPEED
+                    } else {
+                        command.target
+                    }
+                } else {
+                    drone.pos
+                };
+                target.clamp(Vec2::new(0., 0.), Vec2::new(9999., 9999.))
+            })
+            .collect()
+    }
+
+    fn move_creatures(&mut self) {
+        for c in &mut self.creatures {
+            if !c.alive {
+                continue;
+            }
+
+            let (min_y, max_y) = c.habitat();
+            c.pos = c.pos + c.vel;
+
+            if c.pos.x < 0. || c.pos.x > 9999. {
+                if c.typ != -1 && c.vel.len() > FISH_SPEED {
+                    // Frightened fish can swim away from the map.
+                    c.alive = false;
+                    continue;
+                }
+                c.vel.x = -c.vel.x;
+            }
+
+            if c.pos.y < min_y || c.pos.y > max_y {
+                c.vel.y = -c.vel.y;
+            }
+
+            c.pos = c.pos.clamp(Vec2::new(0., min_y), Vec2::new(9999., max_y));
+        }
+    }
+
+    fn move_drones(&mut self, targets: [Vec<Vec2>; 2], monsters_before: &[(Vec2, Vec2)]) {
+        for (player, targets) in self.players.iter_mut().zip(targets) {
+            for (drone, target) in player.drones.iter_mut().zip(targets) {
+                let hit = !drone.emergency
+                    && monsters_before.iter().any(|&(m0, m1)| {
+                        segments_collide(drone.pos, target, m0, m1, MONSTER_KILL_RADIUS)
+                    });
+
+                drone.pos = target;
+
+                if hit {
+                    drone.emergency = true;
+                    drone.light = false;
+                    drone.scans.clear();
+                } else if drone.emergency && drone.pos.y <= 0. {
+                    drone.emergency = false;
+                }
+            }
+        }
+    }
+
+    fn scan_and_save(&mut self) {
+        let creatures = &self.creatures;
+
+        for player in &mut self.players {
+            for drone in &mut player.drones {
+                if drone.emergency {
+                    continue;
+                }
+
+                let radius = drone.light_radius();
+                for c in creatures.iter().filter(|c| c.alive && c.typ != -1) {
+                    if !player.saved.contains(&c.id) && (c.pos - drone.pos).len() <= radius {
+                        drone.scans.insert(c.id);
+                    }
+                }
+            }
+        }
+
+        let mut saved_now: [Vec<i32>; 2] = Default::default();
+        for (p, player) in self.players.iter_mut().enumerate() {
+            for drone in &mut player.drones {
+                if drone.emergency || drone.pos.y > SURFACE {
+                    continue;
+                }
+                for id in drone.scans.drain() {
+                    if !player.saved.contains(&id) && !saved_now[p].contains(&id) {
+                        saved_now[p].push(id);
+                    }
+                }
+            }
+        }
+
+        self.save_scans(saved_now);
+    }
+
+    fn save_scans(&mut self, saved_now: [Vec<i32>; 2]) {
+        let saved_before: [HashSet<i32>; 2] =
+            [self.players[0].saved.clone(), self.players[1].saved.clone()];
+        let types_before: [HashSet<i8>; 2] = [
+            self.players[0].completed_types.clone(),
+            self.players[1].completed_types.clone(),
+        ];
+        let colors_before: [HashSet<i8>; 2] = [
+            self.players[0].completed_colors.clone(),
+            self.players[1].completed_colors.clone(),
+        ];
+
+        for (p, ids) in saved_now.into_iter().enumerate() {
+            let other = 1 - p;
+            let player = &mut self.players[p];
+
+            for id in ids {
+                let c = self.creatures.iter().find(|c| c.id == id).unwrap();
+                let mut points = (c.typ + 1) as i32;
+                if !saved_before[other].contains(&id) {
+                    points *= 2;
+                }
+                player.score += points;
+                player.saved.insert(id);
+            }
+
+            for typ in 0..3 {
+                let count = self
+                    .creatures
+                    .iter()
+                    .filter(|c| c.typ == typ && player.saved.contains(&c.id))
+                    .count();
+                if count == 4 && player.completed_types.insert(typ) {
+                    player.score += if types_before[other].contains(&typ) {
+                        4
+                    } else {
+                        8
+                    };
+                }
+            }
+
+            for color in 0..4 {
+                let count = self
+                    .creatures
+                    .iter()
+                    .filter(|c| c.color == color && player.saved.contains(&c.id))
+                    .count();
+                if count == 3 && player.completed_colors.insert(color) {
+                    player.score += if colors_before[other].contains(&color) {
+                        3
+                    } else {
+                        6
+                    };
+                }
+            }
+        }
+    }
+
+    fn update_creature_velocities(&mut self) {
+        let drones = self
+            .players
+            .iter()
+            .flat_map(|p| p.drones.iter())
+            .filter(|d| !d.emergency)
+            .map(|d| (d.pos, d.light_radius()))
+            .collect::<Vec<_>>();
+
+        let positions = self
+            .creatures
+            .iter()
+            .map(|c| (c.id, c.typ == -1, c.alive, c.pos))
+            .collect::<Vec<_>>();
+
+        for c in &mut self.creatures {
+            if !c.alive {
+                continue;
+            }
+
+            let is_monster = c.typ == -1;
+
+            let closest_drone = drones
+                .iter()
+                .filter(|(pos, radius)| {
+                    let range = if is_monster {
+                        *radius
+                    } else {
+                        FISH_HEAR_RADIUS
+                    };
+                    (*pos - c.pos).len() < range
+                })
+                .min_by_key(|(pos, _)| (*pos - c.pos).len() as i32);
+
+            let neighbour = positions
+                .iter()
+                .filter(|&&(id, monster, alive, pos)| {
+                    id != c.id
+                        && alive
+                        && monster == is_monster
+                        && (pos - c.pos).len() < AVOID_RADIUS
+                })
+                .min_by_key(|(_, _, _, pos)| (*pos - c.pos).len() as i32);
+
+            c.vel = match (closest_drone, neighbour, is_monster) {
+                (Some((pos, _)), _, true) => (*pos - c.pos).norm() * MONSTER_ATTACK_SPEED,
+                (Some((pos, _)), _, false) => (c.pos - *pos).norm() * FISH_FLEE_SPEED,
+                (None, Some((_, _, _, pos)), _) => (c.pos - *pos).norm() * FISH_SPEED,
+                (None, None, true) => c.vel.norm() * MONSTER_SPEED,
+                (None, None, false) => c.vel.norm() * FISH_SPEED,
+            };
+        }
+    }
+
+    /// Plays one turn with the given commands for both players.
+    pub fn step(&mut self, commands: [Vec<DroneCommand>; 2]) {
+        for (player, commands) in commands.iter().enumerate() {
+            self.apply_commands(player, commands);
+        }
+
+        let targets = [
+            self.drone_targets(0, &commands[0]),
+            self.drone_targets(1, &commands[1]),
+        ];
+
+        let monsters_before = self
+            .creatures
+            .iter()
+            .filter(|c| c.typ == -1)
+            .map(|c| c.pos)
+            .collect::<Vec<_>>();
+
+        self.move_creatures();
+
+        let monsters = self
+            .creatures
+            .iter()
+            .filter(|c| c.typ == -1)
+            .zip(monsters_before)
+            .map(|(c, before)| (before, c.pos))
+            .collect::<Vec<_>>();
+
+        self.move_drones(targets, &monsters);
+        self.scan_and_save();
+        self.update_creature_velocities();
+
+        self.turn += 1;
+
+        if self.turn >= MAX_TURNS {
+            self.save_remaining_scans();
+        }
+    }
+
+    fn save_remaining_scans(&mut self) {
+        let mut saved_now: [Vec<i32>; 2] = Default::default();
+
+        for (p, player) in self.players.iter_mut().enumerate() {
+            for drone in &mut player.drones {
+                for id in drone.scans.drain() {
+                    if !player.saved.contains(&id) && !saved_now[p].contains(&id) {
+                        saved_now[p].push(id);
+                    }
+                }
+            }
+        }
+
+        self.save_scans(saved_now);
+    }
+
+    /// Plays a whole game and returns the final scores.
+    pub fn play(&mut self, bots: [&mut dyn Bot; 2]) -> [i32; 2] {
+        let [bot0, bot1] = bots;
+
+        while !self.is_over() {
+            let commands = [
+                bot0.commands(&self.world_for(0)),
+                bot1.commands(&self.world_for(1)),
+            ];
+            self.step(commands);
+        }
+
+        self.scores()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn fish(id: i32, color: i8, typ: i8) -> ArenaCreature {
+        ArenaCreature {
+            id,
+            color,
+            typ,
+            pos: Vec2::new(5000., 2500. * (typ + 1) as f32 + 1000.),
+            vel: Vec2::default(),
+            alive: true,
+        }
+    }
+
+    fn drone(id: i32, pos: Vec2) -> ArenaDrone {
+        ArenaDrone {
+            id,
+            pos,
+            bat: MAX_BATTERY,
+            emergency: false,
+            light: false,
+            scans: HashSet::new(),
+        }
+    }
+
+    fn arena(creatures: Vec<ArenaCreature>) -> Arena {
+        let mut players: [ArenaPlayer; 2] = Default::default();
+        for (p, player) in players.iter_mut().enumerate() {
+            player
+                .drones
+                .push(drone(p as i32, Vec2::new(2000., SURFACE)));
+        }
+
+        Arena {
+            creatures,
+            players,
+            turn: 0,
+        }
+    }
+
+    #[test]
+    fn first_save_doubles_the_points() {
+        let mut arena = arena(vec![fish(4, 0, 1)]);
+
+        arena.save_scans([vec![4], vec![]]);
+        assert_eq!(arena.scores(), [4, 0]);
+
+        arena.save_scans([vec![], vec![4]]);
+        assert_eq!(arena.scores(), [4, 2]);
+    }
+
+    #[test]
+    fn saves_on_the_same_turn_are_both_first() {
+        let mut arena = arena(vec![fish(4, 0, 2)]);
+
+        arena.save_scans([vec![4], vec![4]]);
+        assert_eq!(arena.scores(), [6, 6]);
+    }
+
+    #[test]
+    fn completed_type_bonus() {
+        let mut arena = arena(
+            (0..4)
+                .map(|color| fish(4 + color as i32, color, 0))
+                .collect(),
+        );
+
+        arena.save_scans([vec![4, 5, 6, 7], vec![]]);
+        assert_eq!(arena.scores(), [4 * 2 + 8, 0]);
+
+        arena.save_scans([vec![], vec![4, 5, 6, 7]]);
+        assert_eq!(arena.scores(), [16, 4 + 4]);
+    }
+
+    #[test]
+    fn completed_color_bonus() {
+        let mut arena = arena((0..3).map(|typ| fish(4 + typ as i32, 1, typ)).collect());
+
+        arena.save_scans([vec![4, 5, 6], vec![]]);
+        assert_eq!(arena.scores(), [(1 + 2 + 3) * 2 + 6, 0]);
+    }
+
+    #[test]
+    fn segments_collide_inside_the_kill_radius() {
+        let (a0, a1) = (Vec2::new(0., 0.), Vec2::new(1000., 0.));
+        let still = |pos: Vec2| (pos, pos);
+
+        let (m0, m1) = still(Vec2::new(500., 499.));
+        assert!(segments_collide(a0, a1, m0, m1, MONSTER_KILL_RADIUS));
+
+        let (m0, m1) = still(Vec2::new(500., 501.));
+        assert!(!segments_collide(a0, a1, m0, m1, MONSTER_KILL_RADIUS));
+
+        // Crossing paths only meet in the middle of the turn.
+        let (m0, m1) = (Vec2::new(500., 500.), Vec2::new(500., -500.));
+        assert!(segments_collide(a0, a1, m0, m1, MONSTER_KILL_RADIUS));
+    }
+
+    #[test]
+    fn caught_drone_enters_emergency_and_loses_its_scans() {
+        let mut arena = arena(vec![fish(4, 0, 0)]);
+        arena.players[0].drones[0].scans.insert(4);
+
+        let start = arena.players[0].drones[0].pos;
+        let targets = [vec![start + Vec2::new(600., 0.)], vec![start]];
+        let monster = start + Vec2::new(300., 400.);
+        arena.move_drones(targets, &[(monster, monster)]);
+
+        let caught = &arena.players[0].drones[0];
+        assert!(caught.emergency);
+        assert!(caught.scans.is_empty());
+        assert!(!arena.players[1].drones[0].emergency);
+    }
+}
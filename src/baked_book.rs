@@ -0,0 +1,69 @@
+// Generated by the book binary, do not edit.
+use super::opening_book::*;
+
+pub const BAKED_BOOK: &[&[BookDrone]] = &[
+    &[
+        BookDrone {
+            start: (2000, 500),
+            steps: [
+                (1.3435957, false),
+                (1.5471351, false),
+                (0.9371097, false),
+                (1.9367697, false),
+                (1.5471351, false),
+                (1.3350885, false),
+                (1.200988, true),
+                (1.2879999, true),
+                (1.9609067, true),
+                (1.9367697, false),
+            ],
+        },
+        BookDrone {
+            start: (7000, 500),
+            steps: [
+                (1.8527238, false),
+                (1.8897121, false),
+                (2.069865, false),
+                (2.2847698, false),
+                (2.5841577, false),
+                (2.7980807, false),
+                (2.2847698, false),
+                (1.8935406, false),
+                (1.855818, false),
+                (2.1630852, false),
+            ],
+        },
+    ],
+    &[
+        BookDrone {
+            start: (7999, 500),
+            steps: [
+                (1.343941, false),
+                (1.5760214, false),
+                (1.6754701, false),
+                (1.6754701, false),
+                (1.2937758, false),
+                (1.4797657, true),
+                (0.94403815, false),
+                (0.94403815, false),
+                (0.94403815, false),
+                (1.2939332, false),
+            ],
+        },
+        BookDrone {
+            start: (2999, 500),
+            steps: [
+                (2.0568392, false),
+                (1.584592, false),
+                (1.8487294, false),
+                (2.0438511, false),
+                (1.6896015, true),
+                (1.8199408, false),
+                (1.7945054, false),
+                (1.6896015, true),
+                (1.4845078, true),
+                (0.75401974, true),
+            ],
+        },
+    ],
+];
@@ -0,0 +1,53 @@
+// Generated by build.rs from params/default.params, do not edit.
+use super::params::*;
+
+pub const BAKED_PARAMS: StrategyParams = StrategyParams {
+    planner: PlannerKind::Genetic,
+    visited_score: 0.01,
+    light_threshold: 0.5,
+    scan_risk_weight: 0.2,
+    depth_risk: 1.0,
+    danger_radius: 2500.0,
+    dive_divisor: 5.0,
+    dead_penalty: 1000.0,
+    lost_scans_weight: 1.0,
+    population_size: 30,
+    mutations_size: 30,
+    mutations_count: 3,
+    random_size: 10,
+    crossover_size: 30,
+    search_time_ms: 40,
+    max_generations: 0,
+    beam_width: 4,
+    beam_angles: 8,
+    mcts_angles: 8,
+    mcts_exploration: 0.7,
+    coevolution: false,
+    opponent_population_size: 10,
+    opponent_responses: 2,
+    race_weight: 1.0,
+    combo_weight: 1.0,
+    aggression_weight: 1.0,
+    overlap_weight: 0.5,
+    allocation_bias: 0.5,
+    angle_sigma: 0.5,
+    gaussian_rate: 0.4,
+    replace_rate: 0.2,
+    reverse_rate: 0.1,
+    shift_rate: 0.1,
+    light_toggle_rate: 0.2,
+    uniform_crossover_rate: 0.5,
+    tournament_size: 3,
+    elite_size: 5,
+    checkpoint_interval: 5,
+    encoding: GeneEncoding::Angle,
+    thrust_sigma: 0.2,
+    wait_rate: 0.1,
+    light_repair_rate: 0.3,
+    fallback_budget_ms: 5,
+    repulsion_weight: 2.0,
+    opening_book: true,
+    battery_planner: true,
+    opponent_coverage: false,
+    coverage_weight: 1.0,
+};
@@ -0,0 +1,95 @@
+use super::*;
+
+/// Depth bands of 2500 units: the surface band without fish, then the
+/// habitats of the three fish types.
+pub const DEPTH_BANDS: usize = 4;
+const BAND_SIZE: f32 = 10000. / DEPTH_BANDS as f32;
+
+/// Turns a drone spends in a band on the way down and back up.
+const BAND_TURNS: usize = (2. * BAND_SIZE / DRONE_SPEED) as usize + 1;
+
+pub fn depth_band(y: f32) -> usize {
+    ((y / BAND_SIZE) as usize).min(DEPTH_BANDS - 1)
+}
+
+/// Split of the battery over the next dive of every drone.
+#[derive(Default)]
+pub struct BatteryPlan {
+    /// Lights allowed in each depth band, by plan slot.
+    lights: Vec<[usize; DEPTH_BANDS]>,
+}
+
+impl BatteryPlan {
+    /// Turns of a dive from `y` to the deepest band with fish and back to
+    /// the surface, none if there is nothing left to light.
+    fn dive_turns(y: f32, density: &[f32; DEPTH_BANDS]) -> usize {
+        let Some(deepest) = (0..DEPTH_BANDS).rev().find(|&band| density[band] > 0.) else {
+            return 0;
+        };
+        let bottom = (deepest as f32 + 0.5) * BAND_SIZE;
+
+        (((bottom - y).abs() + bottom) / DRONE_SPEED).ceil() as usize
+    }
+
+    /// A light costs `LIGHT_COST` and the recharge of its turn, the dive
+    /// ends with an empty battery at most.
+    fn split(bat: usize, turns: usize, density: &[f32; DEPTH_BANDS]) -> [usize; DEPTH_BANDS] {
+        let mut lights = [0; DEPTH_BANDS];
+        let total_density = density.iter().sum::<f32>();
+        if total_density <= 0. {
+            return lights;
+        }
+
+        let budget = ((bat + turns) / (LIGHT_COST as usize + 1)).min(turns);
+
+        // Largest remainder apportionment, capped by the turns in a band.
+        let shares = density.map(|d| budget as f32 * d / total_density);
+        for (light, share) in lights.iter_mut().zip(shares) {
+            *light = (share as usize).min(BAND_TURNS);
+        }
+
+        let mut by_remainder = (0..DEPTH_BANDS).collect::<Vec<_>>();
+        by_remainder.sort_by(|&a, &b| shares[b].fract().total_cmp(&shares[a].fract()));
+
+        let mut left = budget - lights.iter().sum::<usize>();
+        for band in by_remainder {
+            if left == 0 {
+                break;
+            }
+            if density[band] > 0. && lights[band] < BAND_TURNS {
+                lights[band] += 1;
+                left -= 1;
+            }
+        }
+
+        lights
+    }
+
+    pub fn update(&mut self, world: &World, drone_score_maps: &[ScoreMap], drone_ids: &[i32]) {
+        self.lights.clear();
+
+        for (id, score_map) in drone_ids.iter().zip(drone_score_maps) {
+            let Some(drone) = world.me.drones.get(id) else {
+                self.lights.push([0; DEPTH_BANDS]);
+                continue;
+            };
+
+            let mut density = [0.; DEPTH_BANDS];
+            for ((x, y), value) in score_map.map.iter() {
+                density[depth_band(ScoreGrid::centre(x, y).y)] += value;
+            }
+
+            let turns = Self::dive_turns(drone.pos.y, &density);
+            self.lights
+                .push(Self::split(drone.bat.max(0) as usize, turns, &density));
+        }
+    }
+
+    /// Lights the drone in `drone_idx` may use in `band`. Drones without a
+    /// plan are not limited.
+    pub fn lights(&self, drone_idx: usize, band: usize) -> usize {
+        self.lights
+            .get(drone_idx)
+            .map_or(usize::MAX, |lights| lights[band])
+    }
+}
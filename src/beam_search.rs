@@ -0,0 +1,116 @@
+use std::time::{Duration, Instant};
+
+use super::*;
+
+#[derive(Clone)]
+struct Node {
+    state: GameState,
+    gene: Gene,
+}
+
+/// Beam search over discretised headings and light for both drones.
+pub struct BeamSearch {
+    params: StrategyParams,
+    beam: Vec<Gene>,
+    expanded: i32,
+}
+
+impl BeamSearch {
+    pub fn new(params: StrategyParams) -> Self {
+        BeamSearch {
+            params,
+            beam: Vec::new(),
+            expanded: 0,
+        }
+    }
+
+    /// Expands every node with all joint option combinations of the drones,
+    /// until `deadline`. The children expanded so far are kept.
+    fn expand(
+        &mut self,
+        simulation: &mut Simulation,
+        beam: &[Node],
+        depth: usize,
+        deadline: Instant,
+    ) -> Vec<Node> {
+        let options = Action::discrete(self.params.beam_angles, self.params.encoding);
+        let drone_count = beam[0].state.drone_count;
+        let joint_count = options.len().pow(drone_count as u32);
+        let mut children = Vec::with_capacity(beam.len() * joint_count);
+
+        'nodes: for node in beam {
+            for joint in 0..joint_count {
+                if Instant::now() >= deadline {
+                    break 'nodes;
+                }
+
+                let mut child = node.clone();
+                let mut actions = Actions::default();
+
+                let mut code = joint;
+                for action in actions.iter_mut().take(drone_count) {
+                    *action = options[code % options.len()];
+                    code /= options.len();
+                }
+
+                simulation.simulate(&mut child.state, &mut actions, depth + 1);
+                child.gene[depth] = actions;
+                children.push(child);
+                self.expanded += 1;
+            }
+        }
+
+        children.sort_by(|a, b| b.state.score.value().total_cmp(&a.state.score.value()));
+        children.truncate(self.params.beam_width.max(1));
+        children
+    }
+}
+
+impl Planner for BeamSearch {
+    fn plan(&mut self, ctx: &PlanningContext, budget: Duration) -> Plan {
+        let start = Instant::now();
+
+        let params = self.params;
+        let mut simulation = Simulation::new(ctx, &params);
+
+        let mut beam = vec![Node {
+            state: GameState::new(ctx),
+            gene: Gene::default(),
+        }];
+
+        self.expanded = 0;
+        let mut depth = 0;
+        let max_depth = match self.params.max_generations {
+            0 => GENE_SIZE,
+            max => max.min(GENE_SIZE),
+        };
+        while depth < max_depth && Instant::now().duration_since(start) < budget {
+            let children = self.expand(&mut simulation, &beam, depth, start + budget);
+            if children.is_empty() {
+                break;
+            }
+            beam = children;
+            depth += 1;
+        }
+
+        self.beam = beam.iter().map(|node| node.gene).collect();
+
+        Plan {
+            actions: beam[0].gene[0],
+            gene: beam[0].gene,
+            score: beam[0].state.score,
+            report: SearchReport {
+                generations: depth,
+                simulations: self.expanded,
+                simulated_steps: simulation.simulated_steps,
+                score: beam[0].state.score,
+                elapsed: start.elapsed(),
+                ..Default::default()
+            },
+        }
+    }
+
+    fn candidates(&self) -> Vec<Gene> {
+        self.beam.clone()
+    }
+}
@@ -0,0 +1,190 @@
+//! Planner benchmark on saved worlds.
+//!
+//! Every `*.world` snapshot in `fixtures` is decided `runs` times by a fresh
+//! strategy with a fixed generation budget and seed, and the time of every
+//! stage is reported along with the simulations per second. Arguments are
+//! `key=value` pairs:
+//!
+//! ```text
+//! cargo run --release --bin bench -- generations=20 out=bench.results 2>/dev/null
+//! cargo run --release --bin bench -- compare=bench.results 2>/dev/null
+//! ```
+//!
+//! `record=bench` plays the arena game of `seed` instead and saves early, mid
+//! and late game snapshots, plus the turn with the most visible monsters.
+extern crate cgbot;
+
+use std::{
+    collections::BTreeMap,
+    fs,
+    path::{Path, PathBuf},
+    time::Duration,
+};
+
+use cgbot::*;
+
+struct Config {
+    fixtures: PathBuf,
+    runs: u64,
+    seed: u64,
+    params: StrategyParams,
+    out: Option<String>,
+    compare: Option<String>,
+    record: Option<String>,
+}
+
+impl Config {
+    fn from_args() -> Self {
+        let args = Args::from_env();
+
+        let mut params = args.params("params", BAKED_PARAMS);
+        params.max_generations = args.value("generations", "20");
+        params.search_time_ms = args.value("search_time_ms", "10000");
+
+        Config {
+            fixtures: args.get("fixtures", "bench").into(),
+            runs: args.value("runs", "5"),
+            seed: args.value("seed", "1"),
+            params,
+            out: args.opt("out"),
+            compare: args.opt("compare"),
+            record: args.opt("record"),
+        }
+    }
+}
+
+/// Metrics by `fixture.metric` key, as written to the results file.
+type Results = BTreeMap<String, f64>;
+
+const METRICS: &[&str] = &[
+    "tracker_us",
+    "bounds_us",
+    "maps_us",
+    "search_us",
+    "sims_per_sec",
+];
+
+fn load_results(path: &str) -> Results {
+    fs::read_to_string(path)
+        .unwrap()
+        .lines()
+        .filter_map(|line| line.split_once('='))
+        .map(|(key, value)| (key.trim().to_string(), value.trim().parse().unwrap()))
+        .collect()
+}
+
+fn bench_fixture(world: &World, config: &Config) -> [f64; 5] {
+    let mut times = StageTimes::default();
+    let mut simulations = 0;
+
+    for run in 0..config.runs {
+        seed_rng(config.seed + run);
+
+        let mut strategy = Strategy::with_params(config.params);
+        strategy.decide(world);
+
+        times.tracker += strategy.times.tracker;
+        times.bounds += strategy.times.bounds;
+        times.maps += strategy.times.maps;
+        times.search += strategy.times.search;
+        simulations += strategy.report.simulations as i64;
+    }
+
+    let per_run = |d: Duration| d.as_secs_f64() * 1e6 / config.runs as f64;
+    [
+        per_run(times.tracker),
+        per_run(times.bounds),
+        per_run(times.maps),
+        per_run(times.search),
+        simulations as f64 / times.search.as_secs_f64(),
+    ]
+}
+
+fn bench(config: &Config) {
+    let mut fixtures = fs::read_dir(&config.fixtures)
+        .unwrap()
+        .map(|entry| entry.unwrap().path())
+        .filter(|path| path.extension().is_some_and(|ext| ext == "world"))
+        .collect::<Vec<_>>();
+    fixtures.sort();
+
+    let previous = config.compare.as_deref().map(load_results);
+    let mut results = Results::new();
+
+    let header = METRICS
+        .iter()
+        .map(|m| format!("{m:>20}"))
+        .collect::<String>();
+    println!("{:<12}{header}", "fixture");
+
+    for path in &fixtures {
+        let name = path.file_stem().unwrap().to_string_lossy();
+        let world = load_snapshot(path).unwrap();
+
+        let mut row = format!("{name:<12}");
+        for (metric, value) in METRICS.iter().zip(bench_fixture(&world, config)) {
+            let key = format!("{name}.{metric}");
+
+            let cell = match previous.as_ref().and_then(|p| p.get(&key)) {
+                Some(old) => format!("{value:.0} ({:+.1}%)", 100. * (value - old) / old),
+                None => format!("{value:.0}"),
+            };
+            row += &format!("{cell:>20}");
+
+            results.insert(key, value);
+        }
+        println!("{row}");
+    }
+
+    if let Some(out) = &config.out {
+        let text = results
+            .iter()
+            .map(|(key, value)| format!("{key} = {value}\n"))
+            .collect::<String>();
+        fs::write(out, text).unwrap();
+    }
+}
+
+fn record(dir: &str, config: &Config) {
+    const TURNS: &[(&str, i32)] = &[("early", 5), ("mid", 60), ("late", 150)];
+
+    let mut params = config.params;
+    params.max_generations = 0;
+    params.search_time_ms = BAKED_PARAMS.search_time_ms;
+
+    let mut players = [Strategy::with_params(params), Strategy::with_params(params)];
+    let mut arena = Arena::new(config.seed);
+    let mut most_monsters = 0;
+
+    while !arena.is_over() {
+        let world = arena.world_for(0);
+
+        let visible_monsters = world
+            .creatures
+            .values()
+            .filter(|c| c.typ == -1 && c.pos.is_some())
+            .count();
+        if visible_monsters > most_monsters {
+            most_monsters = visible_monsters;
+            save_snapshot(&world, &Path::new(dir).join("monsters.world")).unwrap();
+        }
+
+        for (name, turn) in TURNS {
+            if world.iter == *turn {
+                save_snapshot(&world, &Path::new(dir).join(format!("{name}.world"))).unwrap();
+            }
+        }
+
+        let [p0, p1] = &mut players;
+        arena.step([p0.decide(&world), p1.decide(&arena.world_for(1))]);
+    }
+}
+
+fn main() {
+    let config = Config::from_args();
+
+    match &config.record {
+        Some(dir) => record(dir, &config),
+        None => bench(&config),
+    }
+}
@@ -0,0 +1,135 @@
+//! Opening book generator.
+//!
+//! For both sides of the arena, the first turn of `seeds` games is searched
+//! for `search_time_ms` each. Every candidate plan of those searches is then
+//! scored `evals` times on the first turn of every game, and the best on
+//! average becomes the book entry of that start. Arguments are `key=value`
+//! pairs:
+//!
+//! ```text
+//! cargo run --release --bin book -- seeds=16 search_time_ms=2000 2>/dev/null
+//! ```
+//!
+//! The first `BOOK_TURNS` steps are written to `out`, which is bundled into
+//! `merged.rs` with the rest of the library. Lights in the surface band,
+//! where there are no fish, are dropped.
+extern crate cgbot;
+
+use std::{fmt::Write, fs};
+
+use cgbot::*;
+
+struct Config {
+    seeds: u64,
+    evals: usize,
+    params: StrategyParams,
+    out: String,
+}
+
+impl Config {
+    fn from_args() -> Self {
+        let args = Args::from_env();
+
+        let mut params = args.params("params", BAKED_PARAMS);
+        params.search_time_ms = args.value("search_time_ms", "2000");
+        params.opening_book = false;
+
+        Config {
+            seeds: args.value("seeds", "16"),
+            evals: args.value("evals", "40"),
+            params,
+            out: args.get("out", "src/baked_book.rs"),
+        }
+    }
+}
+
+/// Start and book steps of our drones, by plan slot.
+type Entry = Vec<((i32, i32), [BookStep; BOOK_TURNS])>;
+
+fn search_side(side: usize, config: &Config) -> Entry {
+    let worlds = (1..=config.seeds)
+        .map(|seed| Arena::new(seed).world_for(side))
+        .collect::<Vec<_>>();
+
+    let strategies = worlds
+        .iter()
+        .zip(1..)
+        .map(|(world, seed)| {
+            seed_rng(seed);
+            let mut strategy = Strategy::with_params(config.params);
+            strategy.decide(world);
+            strategy
+        })
+        .collect::<Vec<_>>();
+
+    let average = |gene: &Gene| {
+        let total = worlds
+            .iter()
+            .zip(&strategies)
+            .map(|(world, strategy)| {
+                let ctx = strategy.context(world);
+                (0..config.evals)
+                    .map(|_| score_gene(&ctx, &config.params, gene).value())
+                    .sum::<f32>()
+            })
+            .sum::<f32>();
+        total / (worlds.len() * config.evals) as f32
+    };
+
+    let (best, score) = strategies
+        .iter()
+        .flat_map(|strategy| strategy.planner.candidates())
+        .map(|gene| (gene, average(&gene)))
+        .max_by(|a, b| a.1.total_cmp(&b.1))
+        .expect("no candidate plans");
+    eprintln!("side {side}: score {score:.2}");
+
+    let ctx = strategies[0].context(&worlds[0]);
+    ctx.drone_ids
+        .iter()
+        .enumerate()
+        .map(|(drone_idx, id)| {
+            let start = worlds[0].me.drones[id].pos;
+            let mut pos = start;
+            let steps = std::array::from_fn(|step| {
+                let action = best[step][drone_idx];
+                pos = (pos + action.get_move()).clamp(Vec2::new(0., 0.), Vec2::new(9999., 9999.));
+                let (angle, light) = book_step(&action);
+                (angle, light && depth_band(pos.y) > 0)
+            });
+            ((start.x as i32, start.y as i32), steps)
+        })
+        .collect()
+}
+
+fn book_source(entries: &[Entry]) -> String {
+    let mut s = String::from("// Generated by the book binary, do not edit.\n");
+    s += "use super::opening_book::*;\n\n";
+    s += "pub const BAKED_BOOK: &[&[BookDrone]] = &[\n";
+    for entry in entries {
+        s += "    &[\n";
+        for ((x, y), steps) in entry {
+            writeln!(s, "        BookDrone {{").unwrap();
+            writeln!(s, "            start: ({x}, {y}),").unwrap();
+            writeln!(s, "            steps: [").unwrap();
+            for (angle, light) in steps {
+                writeln!(s, "                ({angle:?}, {light}),").unwrap();
+            }
+            writeln!(s, "            ],").unwrap();
+            writeln!(s, "        }},").unwrap();
+        }
+        s += "    ],\n";
+    }
+    s += "];\n";
+    s
+}
+
+fn main() {
+    let config = Config::from_args();
+
+    let entries = (0..2)
+        .map(|side| search_side(side, &config))
+        .collect::<Vec<_>>();
+
+    fs::write(&config.out, book_source(&entries)).unwrap();
+}
@@ -1,6 +1,7 @@
 extern crate cgbot;
 
 use std::collections::{HashMap, HashSet};
+use std::path::Path;
 
 use cgbot::*;
 
@@ -192,8 +193,10 @@ fn draw_world(gc: &mut CanvasGraphicsContext, world: &SimWorld) {
         draw_circle_at_pos(gc, pos, color);
     }
 
-    draw_circle_at_pos(gc, world.drones[0].pos, Color::Rgba(0., 0., 1., 1.));
-    draw_circle_at_pos(gc, world.drones[1].pos, Color::Rgba(1., 0., 1., 1.));
+    for (drone_idx, drone) in world.drones.iter().enumerate() {
+        let red = (drone_idx % 2) as f32;
+        draw_circle_at_pos(gc, drone.pos, Color::Rgba(red, 0., 1., 1.));
+    }
 }
 
 fn draw_lines(gc: &mut CanvasGraphicsContext) {
@@ -216,11 +219,13 @@ fn draw_weights(
     score_map: &ScoreMap,
     exploration_map: &ExplorationMap,
 ) {
+    let exploration_map = exploration_map.on_score_grid();
+
     let mut max: f32 = 0.;
     for x in 0..S_CELLS {
         for y in 0..S_CELLS {
             let score = score_map.get_score_by_idx(x, y);
-            let exploration = exploration_map.get_score_by_idx(x, y);
+            let exploration = exploration_map[(x, y)];
 
             let weight = score * exploration as f32;
 
@@ -231,7 +236,7 @@ fn draw_weights(
     for x in 0..S_CELLS {
         for y in 0..S_CELLS {
             let score = score_map.get_score_by_idx(x, y);
-            let exploration = exploration_map.get_score_by_idx(x, y);
+            let exploration = exploration_map[(x, y)];
 
             let weight = score * exploration;
 
@@ -250,13 +255,11 @@ fn draw_weights(
     }
 }
 
-fn draw_paths(
-    gc: &mut CanvasGraphicsContext,
-    start_positions: [Vec2; 2],
-    pathfinding: &Pathfinding,
-) {
-    for drone_idx in 0..2 {
-        for (i, (_, actions)) in pathfinding.population.iter().enumerate() {
+fn draw_paths(gc: &mut CanvasGraphicsContext, start_positions: &[Vec2], planner: &dyn Planner) {
+    let candidates = planner.candidates();
+
+    for drone_idx in 0..start_positions.len().min(MAX_DRONES) {
+        for (i, actions) in candidates.iter().enumerate() {
             gc.new_path();
 
             let mut pos = start_positions[drone_idx];
@@ -280,6 +283,7 @@ fn draw_paths(
 }
 
 struct App {
+    params: StrategyParams,
     sim_world: SimWorld,
     world: World,
     strategy: Strategy,
@@ -291,12 +295,13 @@ struct App {
 }
 
 impl App {
-    fn new(canvas: Canvas) -> Self {
+    fn new(canvas: Canvas, params: StrategyParams) -> Self {
         let sim_world = SimWorld::gen_random_world();
         let world = sim_world.build_world();
-        let strategy = Strategy::new();
+        let strategy = Strategy::with_params(params);
 
         App {
+            params,
             sim_world,
             world,
             strategy,
@@ -328,11 +333,14 @@ impl App {
             }
 
             if self.draw_paths {
-                draw_paths(
-                    gc,
-                    [self.sim_world.drones[0].pos, self.sim_world.drones[1].pos],
-                    &self.strategy.pathfinding,
-                );
+                let start_positions = self
+                    .sim_world
+                    .drones
+                    .iter()
+                    .map(|drone| drone.pos)
+                    .collect::<Vec<_>>();
+
+                draw_paths(gc, &start_positions, self.strategy.planner.as_ref());
             }
         });
     }
@@ -340,19 +348,28 @@ impl App {
     fn regenerate_map(&mut self) {
         self.sim_world = SimWorld::gen_random_world();
         self.world = self.sim_world.build_world();
-        self.strategy = Strategy::new();
+        self.strategy = Strategy::with_params(self.params);
         self.strategy.play(&self.world);
 
         self.redraw();
     }
 }
 
+fn load_params() -> StrategyParams {
+    match std::env::args().nth(1) {
+        Some(path) => StrategyParams::load(Path::new(&path)).unwrap(),
+        None => BAKED_PARAMS,
+    }
+}
+
 fn main() {
-    with_2d_graphics(|| {
+    let params = load_params();
+
+    with_2d_graphics(move || {
         executor::block_on(async {
             let (canvas, mut events) = create_canvas_window_with_events("CGBOT");
 
-            let mut app = App::new(canvas);
+            let mut app = App::new(canvas, params);
 
             app.redraw();
 
@@ -1,4 +1,913 @@
 pub mod cgbot {
+pub mod allocation {
+use std::collections::HashMap;
+use super::*;
+fn hungarian(cost: &[Vec<f32>]) -> Vec<usize> {
+    let (n, m) = (cost.len(), cost.first().map_or(0, Vec::len));
+    let mut u = vec![0.; n + 1];
+    let mut v = vec![0.; m + 1];
+    let mut row_of = vec![0; m + 1];
+    let mut way = vec![0; m + 1];
+    for row in 1..=n {
+        row_of[0] = row;
+        let mut col = 0;
+        let mut min_to = vec![f32::INFINITY; m + 1];
+        let mut used = vec![false; m + 1];
+        loop {
+            used[col] = true;
+            let r = row_of[col];
+            let (mut delta, mut next) = (f32::INFINITY, 0);
+            for c in 1..=m {
+                if used[c] {
+                    continue;
+                }
+                let reduced = cost[r - 1][c - 1] - u[r] - v[c];
+                if reduced < min_to[c] {
+                    min_to[c] = reduced;
+                    way[c] = col;
+                }
+                if min_to[c] < delta {
+                    delta = min_to[c];
+                    next = c;
+                }
+            }
+            for c in 0..=m {
+                if used[c] {
+                    u[row_of[c]] += delta;
+                    v[c] -= delta;
+                } else {
+                    min_to[c] -= delta;
+                }
+            }
+            col = next;
+            if row_of[col] == 0 {
+                break;
+            }
+        }
+        while col != 0 {
+            let prev = way[col];
+            row_of[col] = row_of[prev];
+            col = prev;
+        }
+    }
+    let mut assignment = vec![0; n];
+    for c in 1..=m {
+        if row_of[c] != 0 {
+            assignment[row_of[c] - 1] = c - 1;
+        }
+    }
+    assignment
+}
+#[derive(Default)]
+pub struct TaskAllocation {
+    owners: HashMap<i32, usize>,
+}
+impl TaskAllocation {
+    pub fn new() -> Self {
+        Self::default()
+    }
+    pub fn update(
+        &mut self,
+        world: &World,
+        bounds_detector: &BoundsDetector,
+        meta_strategy: &MetaStrategy,
+        drone_ids: &[i32],
+    ) {
+        self.owners.clear();
+        let drones = drone_ids
+            .iter()
+            .enumerate()
+            .filter_map(|(slot, id)| world.me.drones.get(id).map(|d| (slot, d)))
+            .filter(|(_, d)| d.emergency == 0)
+            .collect::<Vec<_>>();
+        let mut fish = world
+            .creatures
+            .values()
+            .filter(|c| is_unscanned(c, world))
+            .map(|c| c.id)
+            .collect::<Vec<_>>();
+        fish.sort();
+        if drones.is_empty() || fish.is_empty() {
+            return;
+        }
+        let capacity = fish.len().div_ceil(drones.len());
+        let cost = fish
+            .iter()
+            .map(|&id| {
+                let centre = bounds_detector.get_bounds(id).get_center();
+                let value = meta_strategy.get_fish_cost(id).max(f32::EPSILON);
+                (0..drones.len() * capacity)
+                    .map(|col| {
+                        let (_, drone) = drones[col % drones.len()];
+                        (centre - drone.pos).len() / DRONE_SPEED / value
+                    })
+                    .collect()
+            })
+            .collect::<Vec<Vec<f32>>>();
+        for (id, col) in fish.into_iter().zip(hungarian(&cost)) {
+            self.owners.insert(id, drones[col % drones.len()].0);
+        }
+    }
+    pub fn owner(&self, fish_id: i32) -> Option<usize> {
+        self.owners.get(&fish_id).copied()
+    }
+    pub fn weight(&self, fish_id: i32, drone_idx: usize, bias: f32) -> f32 {
+        match self.owner(fish_id) {
+            Some(owner) if owner != drone_idx => 1. - bias.clamp(0., 1.),
+            _ => 1.,
+        }
+    }
+}
+}
+pub mod arena {
+use std::{
+    collections::{BTreeMap, HashMap, HashSet},
+    f32::consts::PI,
+};
+use rand::{rngs::StdRng, Rng, SeedableRng};
+use super::*;
+const MAX_TURNS: i32 = 200;
+const DRONE_SPEED: f32 = 600.;
+const EMERGENCY_SPEED: f32 = 300.;
+const SINK_SPEED: f32 = 300.;
+const LIGHT_RADIUS: f32 = 800.;
+const POWERED_LIGHT_RADIUS: f32 = 2000.;
+const MAX_BATTERY: i32 = 30;
+const LIGHT_COST: i32 = 5;
+const SURFACE: f32 = 500.;
+const FISH_SPEED: f32 = 200.;
+const FISH_FLEE_SPEED: f32 = 400.;
+const FISH_HEAR_RADIUS: f32 = 1400.;
+const MONSTER_SPEED: f32 = 270.;
+const MONSTER_ATTACK_SPEED: f32 = 540.;
+const MONSTER_KILL_RADIUS: f32 = 500.;
+const MONSTER_VISIBILITY_BONUS: f32 = 300.;
+const AVOID_RADIUS: f32 = 600.;
+pub trait Bot {
+    fn commands(&mut self, world: &World) -> Vec<DroneCommand>;
+}
+impl Bot for Strategy {
+    fn commands(&mut self, world: &World) -> Vec<DroneCommand> {
+        self.decide(world)
+    }
+}
+struct ArenaCreature {
+    id: i32,
+    color: i8,
+    typ: i8,
+    pos: Vec2,
+    vel: Vec2,
+    alive: bool,
+}
+impl ArenaCreature {
+    fn habitat(&self) -> (f32, f32) {
+        match self.typ {
+            -1 => (2500., 9999.),
+            t => (2500. * (t + 1) as f32, 2500. * (t + 2) as f32 - 1.),
+        }
+    }
+}
+struct ArenaDrone {
+    id: i32,
+    pos: Vec2,
+    bat: i32,
+    emergency: bool,
+    light: bool,
+    scans: HashSet<i32>,
+}
+impl ArenaDrone {
+    fn light_radius(&self) -> f32 {
+        if self.light {
+            POWERED_LIGHT_RADIUS
+        } else {
+            LIGHT_RADIUS
+        }
+    }
+}
+#[derive(Default)]
+struct ArenaPlayer {
+    score: i32,
+    saved: HashSet<i32>,
+    completed_types: HashSet<i8>,
+    completed_colors: HashSet<i8>,
+    drones: Vec<ArenaDrone>,
+}
+pub struct Arena {
+    creatures: Vec<ArenaCreature>,
+    players: [ArenaPlayer; 2],
+    turn: i32,
+}
+fn mirror(pos: Vec2) -> Vec2 {
+    Vec2::new(9999. - pos.x, pos.y)
+}
+fn segments_collide(a0: Vec2, a1: Vec2, b0: Vec2, b1: Vec2, radius: f32) -> bool {
+    let p = a0 - b0;
+    let v = (a1 - a0) - (b1 - b0);
+    let vv = v.x * v.x + v.y * v.y;
+    let t = if vv > 0. {
+        (-(p.x * v.x + p.y * v.y) / vv).clamp(0., 1.)
+    } else {
+        0.
+    };
+    (p + v * t).len() < radius
+}
+fn blip_direction(from: Vec2, to: Vec2) -> BlipDirection {
+    match (to.x < from.x, to.y < from.y) {
+        (true, true) => BlipDirection::TL,
+        (true, false) => BlipDirection::BL,
+        (false, true) => BlipDirection::TR,
+        (false, false) => BlipDirection::BR,
+    }
+}
+impl Arena {
+    pub fn new(seed: u64) -> Self {
+        let mut rng = StdRng::seed_from_u64(seed);
+        let mut creatures = Vec::new();
+        for pair in 0..2 {
+            for typ in 0..3 {
+                let (min_y, max_y) = (2500. * (typ + 1) as f32, 2500. * (typ + 2) as f32);
+                let pos = Vec2::new(rng.gen_range(0. ..5000.), rng.gen_range(min_y..max_y));
+                let vel = Vec2::new(1., 0.).rotate(rng.gen_range(-PI..PI)) * FISH_SPEED;
+                for (color, pos, vel) in [
+                    (pair * 2, pos, vel),
+                    (pair * 2 + 1, mirror(pos), Vec2::new(-vel.x, vel.y)),
+                ] {
+                    creatures.push(ArenaCreature {
+                        id: 4 + creatures.len() as i32,
+                        color,
+                        typ,
+                        pos,
+                        vel,
+                        alive: true,
+                    });
+                }
+            }
+        }
+        let monster_pairs = rng.gen_range(1..4);
+        for _ in 0..monster_pairs {
+            let pos = Vec2::new(rng.gen_range(0. ..5000.), rng.gen_range(5000. ..9999.));
+            let vel = Vec2::new(1., 0.).rotate(rng.gen_range(-PI..PI)) * MONSTER_SPEED;
+            for (pos, vel) in [(pos, vel), (mirror(pos), Vec2::new(-vel.x, vel.y))] {
+                creatures.push(ArenaCreature {
+                    id: 4 + creatures.len() as i32,
+                    color: -1,
+                    typ: -1,
+                    pos,
+                    vel,
+                    alive: true,
+                });
+            }
+        }
+        let mut players: [ArenaPlayer; 2] = Default::default();
+        for (i, x) in [2000., 7000.].into_iter().enumerate() {
+            for (p, player) in players.iter_mut().enumerate() {
+                let pos = Vec2::new(x, SURFACE);
+                player.drones.push(ArenaDrone {
+                    id: (i * 2 + p) as i32,
+                    pos: if p == 0 { pos } else { mirror(pos) },
+                    bat: MAX_BATTERY,
+                    emergency: false,
+                    light: false,
+                    scans: HashSet::new(),
+                });
+            }
+        }
+        Arena {
+            creatures,
+            players,
+            turn: 0,
+        }
+    }
+    pub fn scores(&self) -> [i32; 2] {
+        [self.players[0].score, self.players[1].score]
+    }
+    pub fn is_over(&self) -> bool {
+        if self.turn >= MAX_TURNS {
+            return true;
+        }
+        self.creatures
+            .iter()
+            .filter(|c| c.typ != -1 && c.alive)
+            .all(|c| self.players.iter().all(|p| p.saved.contains(&c.id)))
+    }
+    fn is_visible(&self, player: usize, creature: &ArenaCreature) -> bool {
+        let bonus = if creature.typ == -1 {
+            MONSTER_VISIBILITY_BONUS
+        } else {
+            0.
+        };
+        creature.alive
+            && self.players[player]
+                .drones
+                .iter()
+                .filter(|d| !d.emergency)
+                .any(|d| (d.pos - creature.pos).len() < d.light_radius() + bonus)
+    }
+    fn build_player(&self, player: usize, blips: bool) -> Player {
+        let p = &self.players[player];
+        let drones = p
+            .drones
+            .iter()
+            .map(|d| {
+                let blips = if blips {
+                    self.creatures
+                        .iter()
+                        .filter(|c| c.alive)
+                        .map(|c| (c.id, blip_direction(d.pos, c.pos)))
+                        .collect()
+                } else {
+                    HashMap::new()
+                };
+                let drone = Drone {
+                    id: d.id,
+                    pos: Vec2::new(d.pos.x.round(), d.pos.y.round()),
+                    bat: d.bat,
+                    emergency: d.emergency as i32,
+                    blips,
+                    scans: d.scans.clone(),
+                };
+                (d.id, drone)
+            })
+            .collect::<BTreeMap<_, _>>();
+        Player {
+            score: p.score,
+            scans: p.saved.clone(),
+            drones,
+        }
+    }
+    pub fn world_for(&self, player: usize) -> World {
+        let creatures = self
+            .creatures
+            .iter()
+            .map(|c| {
+                let visible = self.is_visible(player, c);
+                let creature = Creature::new(
+                    c.id,
+                    c.color,
+                    c.typ,
+                    visible.then(|| Vec2::new(c.pos.x.round(), c.pos.y.round())),
+                    visible.then(|| Vec2::new(c.vel.x.round(), c.vel.y.round())),
+                );
+                (c.id, creature)
+            })
+            .collect();
+        World {
+            creatures,
+            me: self.build_player(player, true),
+            opponent: self.build_player(1 - player, false),
+            iter: self.turn,
+        }
+    }
+    fn apply_commands(&mut self, player: usize, commands: &[DroneCommand]) {
+        for (drone, command) in self.players[player].drones.iter_mut().zip(commands) {
+            drone.light = false;
+            if drone.emergency {
+                continue;
+            }
+            if command.light && drone.bat >= LIGHT_COST {
+                drone.light = true;
+                drone.bat -= LIGHT_COST;
+            } else {
+                drone.bat = (drone.bat + 1).min(MAX_BATTERY);
+            }
+        }
+    }
+    fn drone_targets(&self, player: usize, commands: &[DroneCommand]) -> Vec<Vec2> {
+        self.players[player]
+            .drones
+            .iter()
+            .enumerate()
+            .map(|(i, drone)| {
+                let target = if drone.emergency {
+                    drone.pos - Vec2::new(0., EMERGENCY_SPEED)
+                } else if commands.get(i).is_some_and(|command| command.wait) {
+                    drone.pos + Vec2::new(0., SINK_SPEED)
+                } else if let Some(command) = commands.get(i) {
+                    let delta = command.target - drone.pos;
+                    if delta.len() > DRONE_SPEED {
+                        drone.pos + delta.norm() * DRONE_SPEED
+                    } else {
+                        command.target
+                    }
+                } else {
+                    drone.pos
+                };
+                target.clamp(Vec2::new(0., 0.), Vec2::new(9999., 9999.))
+            })
+            .collect()
+    }
+    fn move_creatures(&mut self) {
+        for c in &mut self.creatures {
+            if !c.alive {
+                continue;
+            }
+            let (min_y, max_y) = c.habitat();
+            c.pos = c.pos + c.vel;
+            if c.pos.x < 0. || c.pos.x > 9999. {
+                if c.typ != -1 && c.vel.len() > FISH_SPEED {
+                    c.alive = false;
+                    continue;
+                }
+                c.vel.x = -c.vel.x;
+            }
+            if c.pos.y < min_y || c.pos.y > max_y {
+                c.vel.y = -c.vel.y;
+            }
+            c.pos = c.pos.clamp(Vec2::new(0., min_y), Vec2::new(9999., max_y));
+        }
+    }
+    fn move_drones(&mut self, targets: [Vec<Vec2>; 2], monsters_before: &[(Vec2, Vec2)]) {
+        for (player, targets) in self.players.iter_mut().zip(targets) {
+            for (drone, target) in player.drones.iter_mut().zip(targets) {
+                let hit = !drone.emergency
+                    && monsters_before.iter().any(|&(m0, m1)| {
+                        segments_collide(drone.pos, target, m0, m1, MONSTER_KILL_RADIUS)
+                    });
+                drone.pos = target;
+                if hit {
+                    drone.emergency = true;
+                    drone.light = false;
+                    drone.scans.clear();
+                } else if drone.emergency && drone.pos.y <= 0. {
+                    drone.emergency = false;
+                }
+            }
+        }
+    }
+    fn scan_and_save(&mut self) {
+        let creatures = &self.creatures;
+        for player in &mut self.players {
+            for drone in &mut player.drones {
+                if drone.emergency {
+                    continue;
+                }
+                let radius = drone.light_radius();
+                for c in creatures.iter().filter(|c| c.alive && c.typ != -1) {
+                    if !player.saved.contains(&c.id) && (c.pos - drone.pos).len() <= radius {
+                        drone.scans.insert(c.id);
+                    }
+                }
+            }
+        }
+        let mut saved_now: [Vec<i32>; 2] = Default::default();
+        for (p, player) in self.players.iter_mut().enumerate() {
+            for drone in &mut player.drones {
+                if drone.emergency || drone.pos.y > SURFACE {
+                    continue;
+                }
+                for id in drone.scans.drain() {
+                    if !player.saved.contains(&id) && !saved_now[p].contains(&id) {
+                        saved_now[p].push(id);
+                    }
+                }
+            }
+        }
+        self.save_scans(saved_now);
+    }
+    fn save_scans(&mut self, saved_now: [Vec<i32>; 2]) {
+        let saved_before: [HashSet<i32>; 2] =
+            [self.players[0].saved.clone(), self.players[1].saved.clone()];
+        let types_before: [HashSet<i8>; 2] = [
+            self.players[0].completed_types.clone(),
+            self.players[1].completed_types.clone(),
+        ];
+        let colors_before: [HashSet<i8>; 2] = [
+            self.players[0].completed_colors.clone(),
+            self.players[1].completed_colors.clone(),
+        ];
+        for (p, ids) in saved_now.into_iter().enumerate() {
+            let other = 1 - p;
+            let player = &mut self.players[p];
+            for id in ids {
+                let c = self.creatures.iter().find(|c| c.id == id).unwrap();
+                let mut points = (c.typ + 1) as i32;
+                if !saved_before[other].contains(&id) {
+                    points *= 2;
+                }
+                player.score += points;
+                player.saved.insert(id);
+            }
+            for typ in 0..3 {
+                let count = self
+                    .creatures
+                    .iter()
+                    .filter(|c| c.typ == typ && player.saved.contains(&c.id))
+                    .count();
+                if count == 4 && player.completed_types.insert(typ) {
+                    player.score += if types_before[other].contains(&typ) {
+                        4
+                    } else {
+                        8
+                    };
+                }
+            }
+            for color in 0..4 {
+                let count = self
+                    .creatures
+                    .iter()
+                    .filter(|c| c.color == color && player.saved.contains(&c.id))
+                    .count();
+                if count == 3 && player.completed_colors.insert(color) {
+                    player.score += if colors_before[other].contains(&color) {
+                        3
+                    } else {
+                        6
+                    };
+                }
+            }
+        }
+    }
+    fn update_creature_velocities(&mut self) {
+        let drones = self
+            .players
+            .iter()
+            .flat_map(|p| p.drones.iter())
+            .filter(|d| !d.emergency)
+            .map(|d| (d.pos, d.light_radius()))
+            .collect::<Vec<_>>();
+        let positions = self
+            .creatures
+            .iter()
+            .map(|c| (c.id, c.typ == -1, c.alive, c.pos))
+            .collect::<Vec<_>>();
+        for c in &mut self.creatures {
+            if !c.alive {
+                continue;
+            }
+            let is_monster = c.typ == -1;
+            let closest_drone = drones
+                .iter()
+                .filter(|(pos, radius)| {
+                    let range = if is_monster {
+                        *radius
+                    } else {
+                        FISH_HEAR_RADIUS
+                    };
+                    (*pos - c.pos).len() < range
+                })
+                .min_by_key(|(pos, _)| (*pos - c.pos).len() as i32);
+            let neighbour = positions
+                .iter()
+                .filter(|&&(id, monster, alive, pos)| {
+                    id != c.id
+                        && alive
+                        && monster == is_monster
+                        && (pos - c.pos).len() < AVOID_RADIUS
+                })
+                .min_by_key(|(_, _, _, pos)| (*pos - c.pos).len() as i32);
+            c.vel = match (closest_drone, neighbour, is_monster) {
+                (Some((pos, _)), _, true) => (*pos - c.pos).norm() * MONSTER_ATTACK_SPEED,
+                (Some((pos, _)), _, false) => (c.pos - *pos).norm() * FISH_FLEE_SPEED,
+                (None, Some((_, _, _, pos)), _) => (c.pos - *pos).norm() * FISH_SPEED,
+                (None, None, true) => c.vel.norm() * MONSTER_SPEED,
+                (None, None, false) => c.vel.norm() * FISH_SPEED,
+            };
+        }
+    }
+    pub fn step(&mut self, commands: [Vec<DroneCommand>; 2]) {
+        for (player, commands) in commands.iter().enumerate() {
+            self.apply_commands(player, commands);
+        }
+        let targets = [
+            self.drone_targets(0, &commands[0]),
+            self.drone_targets(1, &commands[1]),
+        ];
+        let monsters_before = self
+            .creatures
+            .iter()
+            .filter(|c| c.typ == -1)
+            .map(|c| c.pos)
+            .collect::<Vec<_>>();
+        self.move_creatures();
+        let monsters = self
+            .creatures
+            .iter()
+            .filter(|c| c.typ == -1)
+            .zip(monsters_before)
+            .map(|(c, before)| (before, c.pos))
+            .collect::<Vec<_>>();
+        self.move_drones(targets, &monsters);
+        self.scan_and_save();
+        self.update_creature_velocities();
+        self.turn += 1;
+        if self.turn >= MAX_TURNS {
+            self.save_remaining_scans();
+        }
+    }
+    fn save_remaining_scans(&mut self) {
+        let mut saved_now: [Vec<i32>; 2] = Default::default();
+        for (p, player) in self.players.iter_mut().enumerate() {
+            for drone in &mut player.drones {
+                for id in drone.scans.drain() {
+                    if !player.saved.contains(&id) && !saved_now[p].contains(&id) {
+                        saved_now[p].push(id);
+                    }
+                }
+            }
+        }
+        self.save_scans(saved_now);
+    }
+    pub fn play(&mut self, bots: [&mut dyn Bot; 2]) -> [i32; 2] {
+        let [bot0, bot1] = bots;
+        while !self.is_over() {
+            let commands = [
+                bot0.commands(&self.world_for(0)),
+                bot1.commands(&self.world_for(1)),
+            ];
+            self.step(commands);
+        }
+        self.scores()
+    }
+}
+}
+pub mod baked_book {
+use super::opening_book::*;
+pub const BAKED_BOOK: &[&[BookDrone]] = &[
+    &[
+        BookDrone {
+            start: (2000, 500),
+            steps: [
+                (1.3435957, false),
+                (1.5471351, false),
+                (0.9371097, false),
+                (1.9367697, false),
+                (1.5471351, false),
+                (1.3350885, false),
+                (1.200988, true),
+                (1.2879999, true),
+                (1.9609067, true),
+                (1.9367697, false),
+            ],
+        },
+        BookDrone {
+            start: (7000, 500),
+            steps: [
+                (1.8527238, false),
+                (1.8897121, false),
+                (2.069865, false),
+                (2.2847698, false),
+                (2.5841577, false),
+                (2.7980807, false),
+                (2.2847698, false),
+                (1.8935406, false),
+                (1.855818, false),
+                (2.1630852, false),
+            ],
+        },
+    ],
+    &[
+        BookDrone {
+            start: (7999, 500),
+            steps: [
+                (1.343941, false),
+                (1.5760214, false),
+                (1.6754701, false),
+                (1.6754701, false),
+                (1.2937758, false),
+                (1.4797657, true),
+                (0.94403815, false),
+                (0.94403815, false),
+                (0.94403815, false),
+                (1.2939332, false),
+            ],
+        },
+        BookDrone {
+            start: (2999, 500),
+            steps: [
+                (2.0568392, false),
+                (1.584592, false),
+                (1.8487294, false),
+                (2.0438511, false),
+                (1.6896015, true),
+                (1.8199408, false),
+                (1.7945054, false),
+                (1.6896015, true),
+                (1.4845078, true),
+                (0.75401974, true),
+            ],
+        },
+    ],
+];
+}
+pub mod baked_params {
+use super::params::*;
+pub const BAKED_PARAMS: StrategyParams = StrategyParams {
+    planner: PlannerKind::Genetic,
+    visited_score: 0.01,
+    light_threshold: 0.5,
+    scan_risk_weight: 0.2,
+    depth_risk: 1.0,
+    danger_radius: 2500.0,
+    dive_divisor: 5.0,
+    dead_penalty: 1000.0,
+    lost_scans_weight: 1.0,
+    population_size: 30,
+    mutations_size: 30,
+    mutations_count: 3,
+    random_size: 10,
+    crossover_size: 30,
+    search_time_ms: 40,
+    max_generations: 0,
+    beam_width: 4,
+    beam_angles: 8,
+    mcts_angles: 8,
+    mcts_exploration: 0.7,
+    coevolution: false,
+    opponent_population_size: 10,
+    opponent_responses: 2,
+    race_weight: 1.0,
+    combo_weight: 1.0,
+    aggression_weight: 1.0,
+    overlap_weight: 0.5,
+    allocation_bias: 0.5,
+    angle_sigma: 0.5,
+    gaussian_rate: 0.4,
+    replace_rate: 0.2,
+    reverse_rate: 0.1,
+    shift_rate: 0.1,
+    light_toggle_rate: 0.2,
+    uniform_crossover_rate: 0.5,
+    tournament_size: 3,
+    elite_size: 5,
+    checkpoint_interval: 5,
+    encoding: GeneEncoding::Angle,
+    thrust_sigma: 0.2,
+    wait_rate: 0.1,
+    light_repair_rate: 0.3,
+    fallback_budget_ms: 5,
+    repulsion_weight: 2.0,
+    opening_book: true,
+    battery_planner: true,
+    opponent_coverage: false,
+    coverage_weight: 1.0,
+};
+}
+pub mod battery {
+use super::*;
+pub const DEPTH_BANDS: usize = 4;
+const BAND_SIZE: f32 = 10000. / DEPTH_BANDS as f32;
+const BAND_TURNS: usize = (2. * BAND_SIZE / DRONE_SPEED) as usize + 1;
+pub fn depth_band(y: f32) -> usize {
+    ((y / BAND_SIZE) as usize).min(DEPTH_BANDS - 1)
+}
+#[derive(Default)]
+pub struct BatteryPlan {
+    lights: Vec<[usize; DEPTH_BANDS]>,
+}
+impl BatteryPlan {
+    fn dive_turns(y: f32, density: &[f32; DEPTH_BANDS]) -> usize {
+        let Some(deepest) = (0..DEPTH_BANDS).rev().find(|&band| density[band] > 0.) else {
+            return 0;
+        };
+        let bottom = (deepest as f32 + 0.5) * BAND_SIZE;
+        (((bottom - y).abs() + bottom) / DRONE_SPEED).ceil() as usize
+    }
+    fn split(bat: usize, turns: usize, density: &[f32; DEPTH_BANDS]) -> [usize; DEPTH_BANDS] {
+        let mut lights = [0; DEPTH_BANDS];
+        let total_density = density.iter().sum::<f32>();
+        if total_density <= 0. {
+            return lights;
+        }
+        let budget = ((bat + turns) / (LIGHT_COST as usize + 1)).min(turns);
+        let shares = density.map(|d| budget as f32 * d / total_density);
+        for (light, share) in lights.iter_mut().zip(shares) {
+            *light = (share as usize).min(BAND_TURNS);
+        }
+        let mut by_remainder = (0..DEPTH_BANDS).collect::<Vec<_>>();
+        by_remainder.sort_by(|&a, &b| shares[b].fract().total_cmp(&shares[a].fract()));
+        let mut left = budget - lights.iter().sum::<usize>();
+        for band in by_remainder {
+            if left == 0 {
+                break;
+            }
+            if density[band] > 0. && lights[band] < BAND_TURNS {
+                lights[band] += 1;
+                left -= 1;
+            }
+        }
+        lights
+    }
+    pub fn update(&mut self, world: &World, drone_score_maps: &[ScoreMap], drone_ids: &[i32]) {
+        self.lights.clear();
+        for (id, score_map) in drone_ids.iter().zip(drone_score_maps) {
+            let Some(drone) = world.me.drones.get(id) else {
+                self.lights.push([0; DEPTH_BANDS]);
+                continue;
+            };
+            let mut density = [0.; DEPTH_BANDS];
+            for ((x, y), value) in score_map.map.iter() {
+                density[depth_band(ScoreGrid::centre(x, y).y)] += value;
+            }
+            let turns = Self::dive_turns(drone.pos.y, &density);
+            self.lights
+                .push(Self::split(drone.bat.max(0) as usize, turns, &density));
+        }
+    }
+    pub fn lights(&self, drone_idx: usize, band: usize) -> usize {
+        self.lights
+            .get(drone_idx)
+            .map_or(usize::MAX, |lights| lights[band])
+    }
+}
+}
+pub mod beam_search {
+use std::time::{Duration, Instant};
+use super::*;
+#[derive(Clone)]
+struct Node {
+    state: GameState,
+    gene: Gene,
+}
+pub struct BeamSearch {
+    params: StrategyParams,
+    beam: Vec<Gene>,
+    expanded: i32,
+}
+impl BeamSearch {
+    pub fn new(params: StrategyParams) -> Self {
+        BeamSearch {
+            params,
+            beam: Vec::new(),
+            expanded: 0,
+        }
+    }
+    fn expand(
+        &mut self,
+        simulation: &mut Simulation,
+        beam: &[Node],
+        depth: usize,
+        deadline: Instant,
+    ) -> Vec<Node> {
+        let options = Action::discrete(self.params.beam_angles, self.params.encoding);
+        let drone_count = beam[0].state.drone_count;
+        let joint_count = options.len().pow(drone_count as u32);
+        let mut children = Vec::with_capacity(beam.len() * joint_count);
+        'nodes: for node in beam {
+            for joint in 0..joint_count {
+                if Instant::now() >= deadline {
+                    break 'nodes;
+                }
+                let mut child = node.clone();
+                let mut actions = Actions::default();
+                let mut code = joint;
+                for action in actions.iter_mut().take(drone_count) {
+                    *action = options[code % options.len()];
+                    code /= options.len();
+                }
+                simulation.simulate(&mut child.state, &mut actions, depth + 1);
+                child.gene[depth] = actions;
+                children.push(child);
+                self.expanded += 1;
+            }
+        }
+        children.sort_by(|a, b| b.state.score.value().total_cmp(&a.state.score.value()));
+        children.truncate(self.params.beam_width.max(1));
+        children
+    }
+}
+impl Planner for BeamSearch {
+    fn plan(&mut self, ctx: &PlanningContext, budget: Duration) -> Plan {
+        let start = Instant::now();
+        let params = self.params;
+        let mut simulation = Simulation::new(ctx, &params);
+        let mut beam = vec![Node {
+            state: GameState::new(ctx),
+            gene: Gene::default(),
+        }];
+        self.expanded = 0;
+        let mut depth = 0;
+        let max_depth = match self.params.max_generations {
+            0 => GENE_SIZE,
+            max => max.min(GENE_SIZE),
+        };
+        while depth < max_depth && Instant::now().duration_since(start) < budget {
+            let children = self.expand(&mut simulation, &beam, depth, start + budget);
+            if children.is_empty() {
+                break;
+            }
+            beam = children;
+            depth += 1;
+        }
+        self.beam = beam.iter().map(|node| node.gene).collect();
+        Plan {
+            actions: beam[0].gene[0],
+            gene: beam[0].gene,
+            score: beam[0].state.score,
+            report: SearchReport {
+                generations: depth,
+                simulations: self.expanded,
+                simulated_steps: simulation.simulated_steps,
+                score: beam[0].state.score,
+                elapsed: start.elapsed(),
+                ..Default::default()
+            },
+        }
+    }
+    fn candidates(&self) -> Vec<Gene> {
+        self.beam.clone()
+    }
+}
+}
 pub mod bounds_detector {
 use std::collections::HashMap;
 use super::world::BlipDirection;
@@ -88,71 +997,497 @@ impl BoundsDetector {
     }
 }
 }
-pub mod maps {
-use super::{meta_strategy, Creature, MetaStrategy};
-use super::{bounds_detector::BoundsDetector, vec2::Vec2, world::World};
-const E_CELLS: usize = 20;
-const E_CELL_SIZE: usize = 10000 / E_CELLS;
-pub const S_CELLS: usize = 20;
-pub const S_CELL_SIZE: usize = 10000 / S_CELLS;
-pub struct ExplorationMap {
-    pub map: [[f32; E_CELLS]; E_CELLS],
+pub mod cli {
+use std::{collections::HashMap, fmt::Debug, path::Path, str::FromStr};
+use super::*;
+pub struct Args {
+    values: HashMap<String, String>,
 }
-pub struct ScoreMap {
-    pub map: [[f32; S_CELLS]; S_CELLS],
+impl Args {
+    pub fn from_env() -> Self {
+        Self::parse(std::env::args().skip(1))
+    }
+    pub fn parse(args: impl IntoIterator<Item = String>) -> Self {
+        let values = args
+            .into_iter()
+            .filter_map(|arg| {
+                arg.split_once('=')
+                    .map(|(k, v)| (k.to_string(), v.to_string()))
+            })
+            .collect();
+        Args { values }
+    }
+    pub fn opt(&self, key: &str) -> Option<String> {
+        self.values.get(key).cloned()
+    }
+    pub fn get(&self, key: &str, default: &str) -> String {
+        self.opt(key).unwrap_or(default.into())
+    }
+    pub fn value<T: FromStr>(&self, key: &str, default: &str) -> T
+    where
+        T::Err: Debug,
+    {
+        self.get(key, default).parse().unwrap()
+    }
+    pub fn params(&self, key: &str, default: StrategyParams) -> StrategyParams {
+        match self.values.get(key) {
+            Some(path) => StrategyParams::load(Path::new(path)).unwrap(),
+            None => default,
+        }
+    }
 }
-pub fn position_to_grid_cell(pos: Vec2, cell_size: usize) -> (usize, usize) {
-    (pos.x as usize / cell_size, pos.y as usize / cell_size)
 }
-impl ExplorationMap {
-    pub fn new() -> Self {
-        ExplorationMap {
-            map: [[1.; E_CELLS]; E_CELLS],
+pub mod fallback {
+use std::{f32::consts::PI, time::Instant};
+use super::*;
+fn cell_values(ctx: &PlanningContext) -> ScoreGrid {
+    let exploration = ctx.exploration_map.on_score_grid();
+    ScoreGrid::from_fn(|x, y| exploration[(x, y)] * ctx.score_map.get_score_by_idx(x, y))
+}
+fn attraction(values: &ScoreGrid, pos: Vec2) -> Vec2 {
+    values
+        .iter()
+        .fold(Vec2::default(), |force, ((x, y), value)| {
+            let d = ScoreGrid::centre(x, y) - pos;
+            let dist = d.len().max(ScoreGrid::CELL_WIDTH);
+            force + d * (value / (dist * dist))
+        })
+}
+fn repulsion(tracker: &Tracker, params: &StrategyParams, pos: Vec2) -> Vec2 {
+    tracker.monsters.iter().fold(Vec2::default(), |force, m| {
+        let d = pos - m.pos;
+        let strength = (1. - d.len() / params.danger_radius).max(0.) * params.repulsion_weight;
+        force + d.norm() * strength
+    })
+}
+pub fn potential_field_gene(ctx: &PlanningContext, params: &StrategyParams) -> Gene {
+    let mut gene = Gene::default();
+    let mut values = cell_values(ctx);
+    for (drone_idx, id) in ctx.drone_ids.iter().enumerate().take(MAX_DRONES) {
+        let Some(drone) = ctx.world.me.drones.get(id) else {
+            continue;
+        };
+        let mut pos = drone.pos;
+        for actions in &mut gene {
+            values[ScoreGrid::cell(pos)] = 0.;
+            let dir = attraction(&values, pos).norm() + repulsion(ctx.tracker, params, pos);
+            let angle = if dir.len() > 0. {
+                dir.y.atan2(dir.x)
+            } else {
+                -PI / 2.
+            };
+            let action = Action {
+                angle,
+                light: false,
+                thrust: 1.,
+            };
+            pos = (pos + action.get_move()).clamp(Vec2::new(0., 0.), Vec2::new(9999., 9999.));
+            actions[drone_idx] = action;
         }
     }
-    const STEP: f32 = 0.01;
-    pub fn update(&mut self, world: &World) {
-        for x in 0..E_CELLS {
-            for y in 0..E_CELLS {
-                if self.map[x][y] < 1. {
-                    self.map[x][y] += Self::STEP;
-                }
-            }
+    gene
+}
+pub fn fallback_plan(ctx: &PlanningContext, params: &StrategyParams) -> Plan {
+    let start = Instant::now();
+    let gene = potential_field_gene(ctx, params);
+    Plan {
+        actions: gene[0],
+        gene,
+        score: Score::default(),
+        report: SearchReport {
+            elapsed: start.elapsed(),
+            ..Default::default()
+        },
+    }
+}
+}
+pub mod grid {
+use std::ops::{Index, IndexMut};
+use super::*;
+const MAP_SIZE: f32 = 10000.;
+const COVERAGE_SAMPLES: usize = 4;
+#[derive(Clone, Copy)]
+pub struct Grid<T, const W: usize, const H: usize> {
+    cells: [[T; H]; W],
+}
+impl<T: Copy, const W: usize, const H: usize> Grid<T, W, H> {
+    pub const CELL_WIDTH: f32 = MAP_SIZE / W as f32;
+    pub const CELL_HEIGHT: f32 = MAP_SIZE / H as f32;
+    pub fn filled(value: T) -> Self {
+        Grid {
+            cells: [[value; H]; W],
         }
-        for drone in world.me.drones.values() {
-            let (x, y) = position_to_grid_cell(drone.pos, E_CELL_SIZE);
-            self.map[x][y] = 0.;
-        }
-    }
-    pub fn use_light(&mut self, pos: Vec2) {
-        let (x, y) = position_to_grid_cell(pos, E_CELL_SIZE);
-        let (x, y) = (x as i32, y as i32);
-        for dx in -1..2 {
-            for dy in -1..2 {
-                if x + dx >= 0 && x + dx < E_CELLS as i32 && y + dy >= 0 && y + dy < E_CELLS as i32
-                {
-                    self.map[(x + dx) as usize][(y + dy) as usize] = 0.;
-                }
-            }
+    }
+    pub fn from_fn(mut f: impl FnMut(usize, usize) -> T) -> Self {
+        Grid {
+            cells: std::array::from_fn(|x| std::array::from_fn(|y| f(x, y))),
         }
     }
-    pub fn get_score_by_idx(&self, x: usize, y: usize) -> f32 {
-        self.map[x][y]
+    pub fn fill(&mut self, value: T) {
+        self.cells = [[value; H]; W];
     }
-    pub fn get_score(&self, pos: Vec2) -> f32 {
-        let (x, y) = position_to_grid_cell(pos, E_CELL_SIZE);
-        self.map[x][y]
+    pub fn cell(pos: Vec2) -> (usize, usize) {
+        let x = ((pos.x / Self::CELL_WIDTH) as usize).min(W - 1);
+        let y = ((pos.y / Self::CELL_HEIGHT) as usize).min(H - 1);
+        (x, y)
     }
-}
-impl ScoreMap {
-    pub fn new() -> Self {
-        ScoreMap {
-            map: [[0.; S_CELLS]; S_CELLS],
-        }
+    pub fn corner(x: usize, y: usize) -> Vec2 {
+        Vec2::new(x as f32 * Self::CELL_WIDTH, y as f32 * Self::CELL_HEIGHT)
     }
-    fn is_in_someone_scan(id: i32, world: &World) -> bool {
-        world
-            .me
+    pub fn centre(x: usize, y: usize) -> Vec2 {
+        Vec2::new(
+            (x as f32 + 0.5) * Self::CELL_WIDTH,
+            (y as f32 + 0.5) * Self::CELL_HEIGHT,
+        )
+    }
+    pub fn cells() -> impl Iterator<Item = (usize, usize)> {
+        (0..W).flat_map(|x| (0..H).map(move |y| (x, y)))
+    }
+    pub fn iter(&self) -> impl Iterator<Item = ((usize, usize), T)> + '_ {
+        Self::cells().map(|(x, y)| ((x, y), self.cells[x][y]))
+    }
+    pub fn iter_mut(&mut self) -> impl Iterator<Item = ((usize, usize), &mut T)> {
+        self.cells.iter_mut().enumerate().flat_map(|(x, column)| {
+            column
+                .iter_mut()
+                .enumerate()
+                .map(move |(y, value)| ((x, y), value))
+        })
+    }
+    pub fn neighbours(x: usize, y: usize, reach: usize) -> impl Iterator<Item = (usize, usize)> {
+        let xs = x.saturating_sub(reach)..=(x + reach).min(W - 1);
+        let ys = y.saturating_sub(reach)..=(y + reach).min(H - 1);
+        xs.flat_map(move |cx| ys.clone().map(move |cy| (cx, cy)))
+    }
+    pub fn rect(top_left: Vec2, bot_right: Vec2) -> impl Iterator<Item = (usize, usize)> {
+        let (x0, y0) = Self::cell(top_left);
+        let (x1, y1) = Self::cell(bot_right);
+        (x0..=x1).flat_map(move |x| (y0..=y1).map(move |y| (x, y)))
+    }
+    fn circle_bounds(pos: Vec2, radius: f32) -> impl Iterator<Item = (usize, usize)> {
+        let reach = (radius / Self::CELL_WIDTH.min(Self::CELL_HEIGHT)).ceil() as usize;
+        let (x, y) = Self::cell(pos);
+        Self::neighbours(x, y, reach)
+    }
+    pub fn circle(pos: Vec2, radius: f32) -> impl Iterator<Item = (usize, usize)> {
+        Self::circle_bounds(pos, radius)
+            .filter(move |&(x, y)| (Self::centre(x, y) - pos).len() <= radius)
+    }
+    pub fn circle_coverage(pos: Vec2, radius: f32) -> impl Iterator<Item = ((usize, usize), f32)> {
+        let size = Vec2::new(Self::CELL_WIDTH, Self::CELL_HEIGHT);
+        let step = size * (1. / COVERAGE_SAMPLES as f32);
+        Self::circle_bounds(pos, radius).filter_map(move |(x, y)| {
+            let corner = Self::corner(x, y);
+            let nearest = pos.clamp(corner, corner + size);
+            if (nearest - pos).len() > radius {
+                return None;
+            }
+            let inside = (0..COVERAGE_SAMPLES)
+                .flat_map(|i| (0..COVERAGE_SAMPLES).map(move |j| (i, j)))
+                .filter(|&(i, j)| {
+                    let sample =
+                        corner + Vec2::new((i as f32 + 0.5) * step.x, (j as f32 + 0.5) * step.y);
+                    (sample - pos).len() <= radius
+                })
+                .count();
+            let coverage = inside as f32 / (COVERAGE_SAMPLES * COVERAGE_SAMPLES) as f32;
+            (coverage > 0.).then_some(((x, y), coverage))
+        })
+    }
+}
+impl<const W: usize, const H: usize> Grid<f32, W, H> {
+    pub fn sample(&self, pos: Vec2) -> f32 {
+        let fx = (pos.x / Self::CELL_WIDTH - 0.5).clamp(0., (W - 1) as f32);
+        let fy = (pos.y / Self::CELL_HEIGHT - 0.5).clamp(0., (H - 1) as f32);
+        let (x0, y0) = (fx as usize, fy as usize);
+        let (x1, y1) = ((x0 + 1).min(W - 1), (y0 + 1).min(H - 1));
+        let (tx, ty) = (fx - x0 as f32, fy - y0 as f32);
+        let top = self.cells[x0][y0] * (1. - tx) + self.cells[x1][y0] * tx;
+        let bottom = self.cells[x0][y1] * (1. - tx) + self.cells[x1][y1] * tx;
+        top * (1. - ty) + bottom * ty
+    }
+    pub fn resample<const W2: usize, const H2: usize>(&self) -> Grid<f32, W2, H2> {
+        Grid::from_fn(|x, y| self.sample(Grid::<f32, W2, H2>::centre(x, y)))
+    }
+}
+impl<T, const W: usize, const H: usize> Index<(usize, usize)> for Grid<T, W, H> {
+    type Output = T;
+    fn index(&self, (x, y): (usize, usize)) -> &T {
+        &self.cells[x][y]
+    }
+}
+impl<T, const W: usize, const H: usize> IndexMut<(usize, usize)> for Grid<T, W, H> {
+    fn index_mut(&mut self, (x, y): (usize, usize)) -> &mut T {
+        &mut self.cells[x][y]
+    }
+}
+}
+pub mod input {
+use std::{collections::HashMap, fmt::Write, fs, io::BufRead, path::Path};
+use super::*;
+macro_rules! parse_input {
+    ($x:expr, $t:ident) => {
+        $x.trim().parse::<$t>().unwrap()
+    };
+}
+fn read_line(input: &mut impl BufRead) -> String {
+    let mut line = String::new();
+    input.read_line(&mut line).unwrap();
+    line
+}
+fn read_drones(input: &mut impl BufRead) -> Vec<Drone> {
+    let count = parse_input!(read_line(input), usize);
+    (0..count)
+        .map(|_| {
+            let line = read_line(input);
+            let inputs = line.split(' ').collect::<Vec<_>>();
+            let x = parse_input!(inputs[1], i32) as f32;
+            let y = parse_input!(inputs[2], i32) as f32;
+            Drone {
+                id: parse_input!(inputs[0], i32),
+                pos: Vec2 { x, y },
+                emergency: parse_input!(inputs[3], i32),
+                bat: parse_input!(inputs[4], i32),
+                ..Default::default()
+            }
+        })
+        .collect()
+}
+pub fn read_creatures(input: &mut impl BufRead) -> World {
+    let creature_count = parse_input!(read_line(input), usize);
+    let mut creatures = HashMap::with_capacity(creature_count);
+    for _ in 0..creature_count {
+        let line = read_line(input);
+        let inputs = line.split(' ').collect::<Vec<_>>();
+        let id = parse_input!(inputs[0], i32);
+        let color = parse_input!(inputs[1], i8);
+        let typ = parse_input!(inputs[2], i8);
+        creatures.insert(id, Creature::new(id, color, typ, None, None));
+    }
+    World {
+        creatures,
+        ..Default::default()
+    }
+}
+pub fn read_turn(input: &mut impl BufRead, world: &mut World) {
+    world.clear();
+    world.me.score = parse_input!(read_line(input), i32);
+    world.opponent.score = parse_input!(read_line(input), i32);
+    for player in [&mut world.me, &mut world.opponent] {
+        let scan_count = parse_input!(read_line(input), usize);
+        for _ in 0..scan_count {
+            player.scans.insert(parse_input!(read_line(input), i32));
+        }
+    }
+    for player in [&mut world.me, &mut world.opponent] {
+        for drone in read_drones(input) {
+            player.drones.insert(drone.id, drone);
+        }
+    }
+    let drone_scan_count = parse_input!(read_line(input), usize);
+    for _ in 0..drone_scan_count {
+        let line = read_line(input);
+        let inputs = line.split(' ').collect::<Vec<_>>();
+        let drone_id = parse_input!(inputs[0], i32);
+        let creature_id = parse_input!(inputs[1], i32);
+        if let Some(drone) = world.me.drones.get_mut(&drone_id) {
+            drone.scans.insert(creature_id);
+        } else if let Some(drone) = world.opponent.drones.get_mut(&drone_id) {
+            drone.scans.insert(creature_id);
+        } else {
+            unreachable!()
+        }
+    }
+    for creature in world.creatures.values_mut() {
+        creature.clear();
+    }
+    let visible_creature_count = parse_input!(read_line(input), usize);
+    for _ in 0..visible_creature_count {
+        let line = read_line(input);
+        let inputs = line.split(' ').collect::<Vec<_>>();
+        let id = parse_input!(inputs[0], i32);
+        let x = parse_input!(inputs[1], i32) as f32;
+        let y = parse_input!(inputs[2], i32) as f32;
+        let vx = parse_input!(inputs[3], i32) as f32;
+        let vy = parse_input!(inputs[4], i32) as f32;
+        if let Some(creature) = world.creatures.get_mut(&id) {
+            creature.pos = Some(Vec2 { x, y });
+            creature.speed = Some(Vec2 { x: vx, y: vy });
+        }
+    }
+    let radar_blip_count = parse_input!(read_line(input), usize);
+    for _ in 0..radar_blip_count {
+        let line = read_line(input);
+        let inputs = line.split(' ').collect::<Vec<_>>();
+        let drone_id = parse_input!(inputs[0], i32);
+        let creature_id = parse_input!(inputs[1], i32);
+        if let Some(drone) = world.me.drones.get_mut(&drone_id) {
+            drone
+                .blips
+                .insert(creature_id, BlipDirection::from_str(inputs[2].trim()));
+        }
+    }
+}
+pub fn write_input(world: &World) -> String {
+    let mut s = String::new();
+    let mut creatures = world.creatures.values().collect::<Vec<_>>();
+    creatures.sort_by_key(|c| c.id);
+    writeln!(s, "{}", creatures.len()).unwrap();
+    for c in &creatures {
+        writeln!(s, "{} {} {}", c.id, c.color, c.typ).unwrap();
+    }
+    writeln!(s, "{}\n{}", world.me.score, world.opponent.score).unwrap();
+    for player in [&world.me, &world.opponent] {
+        let mut scans = player.scans.iter().collect::<Vec<_>>();
+        scans.sort();
+        writeln!(s, "{}", scans.len()).unwrap();
+        for id in scans {
+            writeln!(s, "{id}").unwrap();
+        }
+    }
+    for player in [&world.me, &world.opponent] {
+        writeln!(s, "{}", player.drones.len()).unwrap();
+        for d in player.drones.values() {
+            let (x, y) = (d.pos.x as i32, d.pos.y as i32);
+            writeln!(s, "{} {x} {y} {} {}", d.id, d.emergency, d.bat).unwrap();
+        }
+    }
+    let mut drone_scans = Vec::new();
+    for drone in world
+        .me
+        .drones
+        .values()
+        .chain(world.opponent.drones.values())
+    {
+        let mut scans = drone.scans.iter().collect::<Vec<_>>();
+        scans.sort();
+        drone_scans.extend(scans.into_iter().map(|id| (drone.id, id)));
+    }
+    writeln!(s, "{}", drone_scans.len()).unwrap();
+    for (drone_id, creature_id) in drone_scans {
+        writeln!(s, "{drone_id} {creature_id}").unwrap();
+    }
+    let visible = creatures
+        .iter()
+        .filter_map(|c| Some((c.id, c.pos?, c.speed.unwrap_or_default())))
+        .collect::<Vec<_>>();
+    writeln!(s, "{}", visible.len()).unwrap();
+    for (id, pos, speed) in visible {
+        let (x, y, vx, vy) = (pos.x as i32, pos.y as i32, speed.x as i32, speed.y as i32);
+        writeln!(s, "{id} {x} {y} {vx} {vy}").unwrap();
+    }
+    let mut blips = Vec::new();
+    for drone in world.me.drones.values() {
+        let mut drone_blips = drone.blips.iter().collect::<Vec<_>>();
+        drone_blips.sort_by_key(|(id, _)| **id);
+        blips.extend(drone_blips.into_iter().map(|(id, dir)| (drone.id, id, dir)));
+    }
+    writeln!(s, "{}", blips.len()).unwrap();
+    for (drone_id, creature_id, dir) in blips {
+        writeln!(s, "{drone_id} {creature_id} {dir:?}").unwrap();
+    }
+    s
+}
+pub fn save_snapshot(world: &World, path: &Path) -> Result<(), String> {
+    let text = format!("{}\n{}", world.iter, write_input(world));
+    fs::write(path, text).map_err(|e| format!("{}: {e}", path.display()))
+}
+pub fn load_snapshot(path: &Path) -> Result<World, String> {
+    let text = fs::read_to_string(path).map_err(|e| format!("{}: {e}", path.display()))?;
+    let (iter, input) = text
+        .split_once('\n')
+        .ok_or_else(|| format!("{}: empty snapshot", path.display()))?;
+    let mut input = input.as_bytes();
+    let mut world = read_creatures(&mut input);
+    read_turn(&mut input, &mut world);
+    world.iter = iter
+        .trim()
+        .parse()
+        .map_err(|e| format!("{}: {e}", path.display()))?;
+    Ok(world)
+}
+}
+pub mod maps {
+use std::collections::HashMap;
+use super::{
+    meta_strategy, Creature, MetaStrategy, StrategyParams, LIGHT_RADIUS, POWERED_LIGHT_RADIUS,
+};
+use super::{bounds_detector::BoundsDetector, grid::Grid, vec2::Vec2, world::World};
+pub const E_CELLS: usize = 20;
+pub const S_CELLS: usize = 20;
+pub type ExplorationGrid = Grid<f32, E_CELLS, E_CELLS>;
+pub type ScoreGrid = Grid<f32, S_CELLS, S_CELLS>;
+pub struct ExplorationMap {
+    pub map: ExplorationGrid,
+    drone_bat: HashMap<i32, i32>,
+}
+pub struct ScoreMap {
+    pub map: ScoreGrid,
+}
+pub fn is_unscanned(creature: &Creature, world: &World) -> bool {
+    creature.typ != -1
+        && !ScoreMap::is_in_someone_scan(creature.id, world)
+        && ScoreMap::is_alive(creature.id, world)
+        && !world.me.scans.contains(&creature.id)
+}
+impl ExplorationMap {
+    pub fn new() -> Self {
+        ExplorationMap {
+            map: ExplorationGrid::filled(1.),
+            drone_bat: HashMap::new(),
+        }
+    }
+    const STEP: f32 = 0.01;
+    pub fn update(&mut self, world: &World, params: &StrategyParams) {
+        for (_, value) in self.map.iter_mut() {
+            if *value < 1. {
+                *value += Self::STEP;
+            }
+        }
+        let opponent_drones = world
+            .opponent
+            .drones
+            .values()
+            .filter(|_| params.opponent_coverage);
+        for drone in world.me.drones.values().chain(opponent_drones) {
+            let used_light = self
+                .drone_bat
+                .insert(drone.id, drone.bat)
+                .is_some_and(|old_bat| old_bat > drone.bat);
+            if drone.emergency == 1 {
+                continue;
+            }
+            if used_light {
+                self.use_light(drone.pos, params.coverage_weight);
+            } else {
+                self.cover(drone.pos, LIGHT_RADIUS, params.coverage_weight);
+            }
+        }
+    }
+    pub fn use_light(&mut self, pos: Vec2, weight: f32) {
+        self.cover(pos, POWERED_LIGHT_RADIUS, weight);
+    }
+    pub fn cover(&mut self, pos: Vec2, radius: f32, weight: f32) {
+        for (cell, coverage) in ExplorationGrid::circle_coverage(pos, radius) {
+            self.map[cell] *= 1. - weight * coverage;
+        }
+    }
+    pub fn get_score(&self, pos: Vec2) -> f32 {
+        self.map[ExplorationGrid::cell(pos)]
+    }
+    pub fn on_score_grid(&self) -> ScoreGrid {
+        self.map.resample()
+    }
+}
+impl ScoreMap {
+    pub fn new() -> Self {
+        ScoreMap {
+            map: ScoreGrid::filled(0.),
+        }
+    }
+    fn is_in_someone_scan(id: i32, world: &World) -> bool {
+        world
+            .me
             .drones
             .values()
             .into_iter()
@@ -171,37 +1506,221 @@ impl ScoreMap {
         bounds_detector: &BoundsDetector,
         meta_strategy: &MetaStrategy,
     ) {
-        for x in 0..S_CELLS {
-            for y in 0..S_CELLS {
-                self.map[x][y] = 0.;
-            }
-        }
-        let creatures = world.creatures.values().filter(|c| {
-            c.typ != -1
-                && !Self::is_in_someone_scan(c.id, world)
-                && Self::is_alive(c.id, world)
-                && !world.me.scans.contains(&c.id)
-        });
+        self.update_weighted(world, bounds_detector, meta_strategy, |_| 1.);
+    }
+    pub fn update_weighted(
+        &mut self,
+        world: &World,
+        bounds_detector: &BoundsDetector,
+        meta_strategy: &MetaStrategy,
+        weight: impl Fn(i32) -> f32,
+    ) {
+        self.map.fill(0.);
+        let creatures = world.creatures.values().filter(|c| is_unscanned(c, world));
         for c in creatures {
             let bounds = bounds_detector.get_bounds(c.id);
-            let creature_cost = meta_strategy.get_fish_cost(c.id);
-            let (start_x, start_y) = position_to_grid_cell(bounds.top_left, S_CELL_SIZE);
-            let (end_x, end_y) = position_to_grid_cell(bounds.bot_right, S_CELL_SIZE);
-            let (end_x, end_y) = ((end_x + 1).min(S_CELLS), (end_y + 1).min(S_CELLS));
-            let cells_count = (end_x - start_x) * (end_y - start_y);
-            for x in start_x..end_x {
-                for y in start_y..end_y {
-                    self.map[x][y] += creature_cost / (cells_count as f32)
-                }
+            let creature_cost = meta_strategy.get_fish_cost(c.id) * weight(c.id);
+            let cells_count = ScoreGrid::rect(bounds.top_left, bounds.bot_right).count();
+            for cell in ScoreGrid::rect(bounds.top_left, bounds.bot_right) {
+                self.map[cell] += creature_cost / (cells_count as f32)
             }
         }
     }
     pub fn get_score(&self, pos: Vec2) -> f32 {
-        let (x, y) = position_to_grid_cell(pos, S_CELL_SIZE);
-        self.map[x][y]
+        self.map[ScoreGrid::cell(pos)]
     }
     pub fn get_score_by_idx(&self, x: usize, y: usize) -> f32 {
-        self.map[x][y]
+        self.map[(x, y)]
+    }
+}
+}
+pub mod mcts {
+use std::{
+    collections::HashMap,
+    time::{Duration, Instant},
+};
+use rand::Rng;
+use super::*;
+#[derive(Default, Clone, Copy)]
+struct Arm {
+    visits: u32,
+    total: f32,
+}
+impl Arm {
+    fn mean(&self) -> f32 {
+        self.total / self.visits as f32
+    }
+}
+struct Node {
+    visits: u32,
+    arms: Vec<Vec<Arm>>,
+    children: HashMap<JointArms, usize>,
+}
+type JointArms = [usize; MAX_DRONES];
+impl Node {
+    fn new(drone_count: usize, arms_count: usize) -> Self {
+        Node {
+            visits: 0,
+            arms: vec![vec![Arm::default(); arms_count]; drone_count],
+            children: HashMap::new(),
+        }
+    }
+}
+pub struct Mcts {
+    params: StrategyParams,
+    options: Vec<Action>,
+    nodes: Vec<Node>,
+    drone_count: usize,
+    best_by_root_arms: HashMap<JointArms, (f32, Gene)>,
+}
+impl Mcts {
+    pub fn new(params: StrategyParams) -> Self {
+        Mcts {
+            params,
+            options: Action::discrete(params.mcts_angles, params.encoding),
+            nodes: Vec::new(),
+            drone_count: 0,
+            best_by_root_arms: HashMap::new(),
+        }
+    }
+    fn select_arm(&self, node: &Node, drone: usize) -> usize {
+        let arms = &node.arms[drone];
+        let unvisited = arms
+            .iter()
+            .enumerate()
+            .filter(|(_, arm)| arm.visits == 0)
+            .map(|(i, _)| i)
+            .collect::<Vec<_>>();
+        if !unvisited.is_empty() {
+            return unvisited[planner_rng().gen_range(0..unvisited.len())];
+        }
+        let min = arms.iter().map(Arm::mean).fold(f32::MAX, f32::min);
+        let max = arms.iter().map(Arm::mean).fold(f32::MIN, f32::max);
+        let spread = if max > min { max - min } else { 1. };
+        let c = self.params.mcts_exploration * spread;
+        let ln_n = (node.visits.max(1) as f32).ln();
+        let ucb = |arm: &Arm| arm.mean() + c * (ln_n / arm.visits as f32).sqrt();
+        (0..arms.len())
+            .max_by(|&a, &b| ucb(&arms[a]).total_cmp(&ucb(&arms[b])))
+            .unwrap()
+    }
+    fn random_actions(&self) -> Actions {
+        let mut rng = planner_rng();
+        [(); MAX_DRONES].map(|_| self.options[rng.gen_range(0..self.options.len())])
+    }
+    fn new_node(&self) -> Node {
+        Node::new(self.drone_count, self.options.len())
+    }
+    fn iterate(&mut self, simulation: &mut Simulation, state_proto: &GameState) {
+        let mut state = state_proto.clone();
+        let mut gene = Gene::default();
+        let mut path = Vec::new();
+        let mut node = 0;
+        let mut depth = 0;
+        while depth < GENE_SIZE {
+            let mut arms = JointArms::default();
+            let mut actions = Actions::default();
+            for drone in 0..self.drone_count {
+                arms[drone] = self.select_arm(&self.nodes[node], drone);
+                actions[drone] = self.options[arms[drone]];
+            }
+            simulation.simulate(&mut state, &mut actions, depth + 1);
+            gene[depth] = actions;
+            depth += 1;
+            path.push((node, arms));
+            if let Some(&child) = self.nodes[node].children.get(&arms) {
+                node = child;
+            } else {
+                let child = self.nodes.len();
+                self.nodes.push(self.new_node());
+                self.nodes[node].children.insert(arms, child);
+                break;
+            }
+        }
+        while depth < GENE_SIZE {
+            let mut actions = self.random_actions();
+            simulation.simulate(&mut state, &mut actions, depth + 1);
+            gene[depth] = actions;
+            depth += 1;
+        }
+        simulation.total_simulations += 1;
+        let reward = state.score.value();
+        let root_arms = path[0].1;
+        let best = self
+            .best_by_root_arms
+            .entry(root_arms)
+            .or_insert((f32::MIN, gene));
+        if reward > best.0 {
+            *best = (reward, gene);
+        }
+        for (node, arms) in path {
+            let node = &mut self.nodes[node];
+            node.visits += 1;
+            for (drone_arms, &arm) in node.arms.iter_mut().zip(&arms) {
+                drone_arms[arm].visits += 1;
+                drone_arms[arm].total += reward;
+            }
+        }
+    }
+    fn most_visited(&self, drone: usize) -> usize {
+        let arms = &self.nodes[0].arms[drone];
+        (0..arms.len()).max_by_key(|&i| arms[i].visits).unwrap()
+    }
+}
+impl Planner for Mcts {
+    fn plan(&mut self, ctx: &PlanningContext, budget: Duration) -> Plan {
+        let start = Instant::now();
+        let params = self.params;
+        let mut simulation = Simulation::new(ctx, &params);
+        let state_proto = GameState::new(ctx);
+        self.drone_count = state_proto.drone_count;
+        self.nodes.clear();
+        self.nodes.push(self.new_node());
+        self.best_by_root_arms.clear();
+        loop {
+            self.iterate(&mut simulation, &state_proto);
+            let max = self.params.max_generations;
+            if Instant::now().duration_since(start) >= budget
+                || (max != 0 && self.nodes[0].visits as usize >= max)
+            {
+                break;
+            }
+        }
+        let mut arms = JointArms::default();
+        for (drone, arm) in arms.iter_mut().enumerate().take(self.drone_count) {
+            *arm = self.most_visited(drone);
+        }
+        let (_, gene) = match self.best_by_root_arms.get(&arms) {
+            Some(&best) => best,
+            None => self
+                .best_by_root_arms
+                .values()
+                .copied()
+                .max_by(|a, b| a.0.total_cmp(&b.0))
+                .unwrap(),
+        };
+        let mut state = state_proto.clone();
+        let mut replay = gene;
+        simulation.simulate_all(&mut state, &mut replay);
+        Plan {
+            actions: replay[0],
+            gene: replay,
+            score: state.score,
+            report: SearchReport {
+                generations: self.nodes[0].visits as usize,
+                simulations: simulation.total_simulations,
+                dead_simulations: simulation.dead_simulations,
+                simulated_steps: simulation.simulated_steps,
+                score: state.score,
+                elapsed: start.elapsed(),
+                ..Default::default()
+            },
+        }
+    }
+    fn candidates(&self) -> Vec<Gene> {
+        let mut best = self.best_by_root_arms.values().collect::<Vec<_>>();
+        best.sort_by(|a, b| b.0.total_cmp(&a.0));
+        best.into_iter().map(|(_, gene)| *gene).collect()
     }
 }
 }
@@ -260,69 +1779,1299 @@ impl MetaStrategy {
         }
         cost
     }
-    pub fn update(&mut self, world: &World) {
-        let fishes = world.creatures.values().filter(|c| c.typ != -1);
-        for f in fishes {
-            self.fish_cost
-                .insert(f.id, Self::calculate_creature_cost(f, world));
-        }
+    pub fn update(&mut self, world: &World) {
+        let fishes = world.creatures.values().filter(|c| c.typ != -1);
+        for f in fishes {
+            self.fish_cost
+                .insert(f.id, Self::calculate_creature_cost(f, world));
+        }
+    }
+    pub fn get_fish_cost(&self, id: i32) -> f32 {
+        *self.fish_cost.get(&id).unwrap()
+    }
+}
+}
+pub mod opening_book {
+use std::time::Instant;
+use super::*;
+pub const BOOK_TURNS: usize = 10;
+const SAFETY_TURNS: usize = 3;
+const START_TOLERANCE: f32 = 50.;
+pub type BookStep = (f32, bool);
+pub fn book_step(action: &Action) -> BookStep {
+    (action.angle, action.light)
+}
+pub struct BookDrone {
+    pub start: (i32, i32),
+    pub steps: [BookStep; BOOK_TURNS],
+}
+pub struct Opening {
+    lines: Vec<&'static [BookStep; BOOK_TURNS]>,
+}
+impl Opening {
+    pub fn find(world: &World, drone_ids: &[i32]) -> Option<Self> {
+        BAKED_BOOK.iter().find_map(|entry| {
+            let lines = drone_ids
+                .iter()
+                .map(|id| {
+                    let pos = world.me.drones.get(id)?.pos;
+                    entry.iter().find_map(|drone| {
+                        let start = Vec2::new(drone.start.0 as f32, drone.start.1 as f32);
+                        ((start - pos).len() <= START_TOLERANCE).then_some(&drone.steps)
+                    })
+                })
+                .collect::<Option<Vec<_>>>()?;
+            (lines.len() == entry.len()).then_some(Opening { lines })
+        })
+    }
+    pub fn plan(
+        &self,
+        ctx: &PlanningContext,
+        params: &StrategyParams,
+        turn: usize,
+    ) -> Option<Plan> {
+        if turn >= BOOK_TURNS {
+            return None;
+        }
+        let start = Instant::now();
+        let mut gene = Gene::default();
+        for (drone_idx, (id, steps)) in ctx.drone_ids.iter().zip(&self.lines).enumerate() {
+            let drone = ctx.world.me.drones.get(id)?;
+            if drone.emergency != 0 {
+                return None;
+            }
+            let mut pos = drone.pos;
+            for (step, (actions, &(angle, light))) in
+                gene.iter_mut().zip(&steps[turn..]).enumerate()
+            {
+                let action = Action {
+                    angle,
+                    light,
+                    thrust: 1.,
+                };
+                pos = (pos + action.get_move()).clamp(Vec2::new(0., 0.), Vec2::new(9999., 9999.));
+                if step < SAFETY_TURNS && ctx.tracker.is_dangerous(pos) {
+                    return None;
+                }
+                actions[drone_idx] = action;
+            }
+        }
+        let mut simulation = Simulation::new(ctx, params);
+        let mut state = GameState::new(ctx);
+        simulation.simulate_all(&mut state, &mut gene);
+        Some(Plan {
+            actions: gene[0],
+            gene,
+            score: state.score,
+            report: SearchReport {
+                simulations: simulation.total_simulations,
+                simulated_steps: simulation.simulated_steps,
+                score: state.score,
+                elapsed: start.elapsed(),
+                ..Default::default()
+            },
+        })
+    }
+}
+}
+pub mod params {
+use std::{fmt::Debug, fs, path::Path};
+pub trait ParamValue: Sized + Debug {
+    fn parse_value(s: &str) -> Option<Self>;
+    fn to_f32(&self) -> f32;
+    fn from_f32(v: f32) -> Self;
+    fn to_source(&self) -> String {
+        format!("{:?}", self)
+    }
+    fn to_param(&self) -> String {
+        self.to_source()
+    }
+}
+impl ParamValue for f32 {
+    fn parse_value(s: &str) -> Option<Self> {
+        s.parse().ok()
+    }
+    fn to_f32(&self) -> f32 {
+        *self
+    }
+    fn from_f32(v: f32) -> Self {
+        v
+    }
+}
+impl ParamValue for usize {
+    fn parse_value(s: &str) -> Option<Self> {
+        s.parse().ok()
+    }
+    fn to_f32(&self) -> f32 {
+        *self as f32
+    }
+    fn from_f32(v: f32) -> Self {
+        v.round().max(0.) as usize
+    }
+}
+impl ParamValue for u64 {
+    fn parse_value(s: &str) -> Option<Self> {
+        s.parse().ok()
+    }
+    fn to_f32(&self) -> f32 {
+        *self as f32
+    }
+    fn from_f32(v: f32) -> Self {
+        v.round().max(0.) as u64
+    }
+}
+impl ParamValue for bool {
+    fn parse_value(s: &str) -> Option<Self> {
+        match s {
+            "true" | "1" => Some(true),
+            "false" | "0" => Some(false),
+            _ => None,
+        }
+    }
+    fn to_f32(&self) -> f32 {
+        *self as i32 as f32
+    }
+    fn from_f32(v: f32) -> Self {
+        v >= 0.5
+    }
+}
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum PlannerKind {
+    Genetic,
+    Beam,
+    Mcts,
+}
+impl ParamValue for PlannerKind {
+    fn parse_value(s: &str) -> Option<Self> {
+        match s {
+            "genetic" => Some(PlannerKind::Genetic),
+            "beam" => Some(PlannerKind::Beam),
+            "mcts" => Some(PlannerKind::Mcts),
+            _ => None,
+        }
+    }
+    fn to_f32(&self) -> f32 {
+        *self as usize as f32
+    }
+    fn from_f32(v: f32) -> Self {
+        match v.round() as usize {
+            0 => PlannerKind::Genetic,
+            1 => PlannerKind::Beam,
+            _ => PlannerKind::Mcts,
+        }
+    }
+    fn to_source(&self) -> String {
+        format!("PlannerKind::{:?}", self)
+    }
+    fn to_param(&self) -> String {
+        match self {
+            PlannerKind::Genetic => "genetic".to_string(),
+            PlannerKind::Beam => "beam".to_string(),
+            PlannerKind::Mcts => "mcts".to_string(),
+        }
+    }
+}
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum GeneEncoding {
+    Angle,
+    Thrust,
+}
+impl ParamValue for GeneEncoding {
+    fn parse_value(s: &str) -> Option<Self> {
+        match s {
+            "angle" => Some(GeneEncoding::Angle),
+            "thrust" => Some(GeneEncoding::Thrust),
+            _ => None,
+        }
+    }
+    fn to_f32(&self) -> f32 {
+        *self as usize as f32
+    }
+    fn from_f32(v: f32) -> Self {
+        match v.round() as usize {
+            0 => GeneEncoding::Angle,
+            _ => GeneEncoding::Thrust,
+        }
+    }
+    fn to_source(&self) -> String {
+        format!("GeneEncoding::{:?}", self)
+    }
+    fn to_param(&self) -> String {
+        match self {
+            GeneEncoding::Angle => "angle".to_string(),
+            GeneEncoding::Thrust => "thrust".to_string(),
+        }
+    }
+}
+macro_rules! strategy_params {
+    ($($name:ident: $t:ty = $default:expr,)*) => {
+        #[derive(Debug, Clone, Copy, PartialEq)]
+        pub struct StrategyParams {
+            $(pub $name: $t,)*
+        }
+        impl Default for StrategyParams {
+            fn default() -> Self {
+                StrategyParams {
+                    $($name: $default,)*
+                }
+            }
+        }
+        impl StrategyParams {
+            pub fn get_f32(&self, key: &str) -> Option<f32> {
+                match key {
+                    $(stringify!($name) => Some(self.$name.to_f32()),)*
+                    _ => None,
+                }
+            }
+            pub fn set_f32(&mut self, key: &str, value: f32) -> Result<(), String> {
+                match key {
+                    $(stringify!($name) => self.$name = <$t as ParamValue>::from_f32(value),)*
+                    _ => return Err(format!("unknown parameter: {key}")),
+                }
+                Ok(())
+            }
+            pub fn set(&mut self, key: &str, value: &str) -> Result<(), String> {
+                match key {
+                    $(stringify!($name) => {
+                        self.$name = <$t as ParamValue>::parse_value(value)
+                            .ok_or_else(|| format!("bad value for {key}: {value}"))?;
+                    })*
+                    _ => return Err(format!("unknown parameter: {key}")),
+                }
+                Ok(())
+            }
+            pub fn to_file_string(&self) -> String {
+                let mut s = String::new();
+                $(s += &format!("{} = {}\n", stringify!($name), self.$name.to_param());)*
+                s
+            }
+            pub fn to_baked_source(&self, origin: &str) -> String {
+                let mut s = format!("// Generated by build.rs from {origin}, do not edit.\n");
+                s += "use super::params::*;\n\n";
+                s += "pub const BAKED_PARAMS: StrategyParams = StrategyParams {\n";
+                $(s += &format!("    {}: {},\n", stringify!($name), self.$name.to_source());)*
+                s += "};\n";
+                s
+            }
+        }
+    };
+}
+strategy_params! {
+    planner: PlannerKind = PlannerKind::Genetic,
+    visited_score: f32 = 0.01,
+    light_threshold: f32 = 0.5,
+    scan_risk_weight: f32 = 0.2,
+    depth_risk: f32 = 1.,
+    danger_radius: f32 = 2500.,
+    dive_divisor: f32 = 5.,
+    dead_penalty: f32 = 1000.,
+    lost_scans_weight: f32 = 1.,
+    population_size: usize = 30,
+    mutations_size: usize = 30,
+    mutations_count: usize = 3,
+    random_size: usize = 10,
+    crossover_size: usize = 30,
+    search_time_ms: u64 = 40,
+    max_generations: usize = 0,
+    beam_width: usize = 4,
+    beam_angles: usize = 8,
+    mcts_angles: usize = 8,
+    mcts_exploration: f32 = 0.7,
+    coevolution: bool = false,
+    opponent_population_size: usize = 10,
+    opponent_responses: usize = 2,
+    race_weight: f32 = 1.,
+    combo_weight: f32 = 1.,
+    aggression_weight: f32 = 1.,
+    overlap_weight: f32 = 0.5,
+    allocation_bias: f32 = 0.5,
+    angle_sigma: f32 = 0.5,
+    gaussian_rate: f32 = 0.4,
+    replace_rate: f32 = 0.2,
+    reverse_rate: f32 = 0.1,
+    shift_rate: f32 = 0.1,
+    light_toggle_rate: f32 = 0.2,
+    uniform_crossover_rate: f32 = 0.5,
+    tournament_size: usize = 3,
+    elite_size: usize = 5,
+    checkpoint_interval: usize = 5,
+    encoding: GeneEncoding = GeneEncoding::Angle,
+    thrust_sigma: f32 = 0.2,
+    wait_rate: f32 = 0.1,
+    light_repair_rate: f32 = 0.3,
+    fallback_budget_ms: u64 = 5,
+    repulsion_weight: f32 = 2.,
+    opening_book: bool = true,
+    battery_planner: bool = true,
+    opponent_coverage: bool = false,
+    coverage_weight: f32 = 1.,
+}
+impl StrategyParams {
+    pub fn parse(text: &str) -> Result<Self, String> {
+        let mut params = StrategyParams::default();
+        for line in text.lines() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let (key, value) = line
+                .split_once('=')
+                .ok_or_else(|| format!("expected key = value: {line}"))?;
+            params.set(key.trim(), value.trim())?;
+        }
+        Ok(params)
+    }
+    pub fn load(path: &Path) -> Result<Self, String> {
+        let text = fs::read_to_string(path).map_err(|e| format!("{}: {e}", path.display()))?;
+        Self::parse(&text)
+    }
+}
+}
+pub mod pathfinding {
+use std::{
+    f32::consts::PI,
+    time::{Duration, Instant},
+};
+use rand::Rng;
+use super::*;
+pub type DronePlan = [Action; GENE_SIZE];
+const REPAIR_STEPS: usize = 3;
+#[derive(Clone, Default)]
+pub struct Individual {
+    pub score: Score,
+    pub plan: DronePlan,
+    fitness: f32,
+    id: u64,
+    gene: Gene,
+    checkpoints: Vec<StateHandle>,
+}
+pub struct Pathfinding {
+    pub individuals: Vec<Individual>,
+    free: Vec<usize>,
+    pub populations: Vec<Vec<usize>>,
+    drone_ids: Vec<i32>,
+    spare: Vec<usize>,
+    best: Option<(Score, Gene)>,
+    partners: Vec<Vec<u64>>,
+    next_id: u64,
+    opponents: Vec<(f32, Gene)>,
+    params: StrategyParams,
+}
+fn gaussian(rng: &mut impl Rng) -> f32 {
+    let u1 = rng.gen_range(f32::EPSILON..1.);
+    let u2 = rng.gen::<f32>();
+    (-2. * u1.ln()).sqrt() * (2. * PI * u2).cos()
+}
+fn tournament(population_size: usize, tournament_size: usize, rng: &mut impl Rng) -> usize {
+    (0..tournament_size.max(1))
+        .map(|_| rng.gen_range(0..population_size))
+        .min()
+        .unwrap()
+}
+fn set_plan(gene: &mut Gene, drone_idx: usize, plan: &DronePlan) {
+    for (actions, action) in gene.iter_mut().zip(plan) {
+        actions[drone_idx] = *action;
+    }
+}
+fn split(gene: &Gene, drone_idx: usize) -> DronePlan {
+    let mut plan = DronePlan::default();
+    for (i, actions) in gene.iter().enumerate() {
+        plan[i] = actions[drone_idx];
+    }
+    plan
+}
+impl Pathfinding {
+    pub fn new(params: StrategyParams) -> Self {
+        Pathfinding {
+            individuals: Vec::new(),
+            free: Vec::new(),
+            populations: Vec::new(),
+            drone_ids: Vec::new(),
+            spare: Vec::new(),
+            best: None,
+            partners: Vec::new(),
+            next_id: 0,
+            opponents: Vec::new(),
+            params,
+        }
+    }
+    fn evaluate(
+        &self,
+        simulation: &mut Simulation,
+        state_proto: &GameState,
+        gene: &mut Gene,
+        parent: &[StateHandle],
+        from: usize,
+        checkpoints: &mut Vec<StateHandle>,
+    ) -> Score {
+        if !self.params.coevolution || self.opponents.is_empty() {
+            return simulation
+                .simulate_cached(state_proto, gene, parent, from, checkpoints)
+                .score;
+        }
+        let mut worst: Option<(Score, Gene)> = None;
+        for (_, opponent_gene) in self
+            .opponents
+            .iter()
+            .take(self.params.opponent_responses.max(1))
+        {
+            let mut candidate = *gene;
+            let mut state = state_proto.clone();
+            simulation.simulate_all_against(&mut state, &mut candidate, opponent_gene);
+            let is_worse = match &worst {
+                Some((score, _)) => state.score.value() < score.value(),
+                None => true,
+            };
+            if is_worse {
+                worst = Some((state.score, candidate));
+            }
+        }
+        let (score, adjusted) = worst.unwrap();
+        *gene = adjusted;
+        score
+    }
+    fn with_partners(&self, drone_idx: usize, plan: &DronePlan) -> Gene {
+        let mut gene = self.straight_top_gene();
+        for (i, population) in self.populations.iter().enumerate() {
+            if let Some(&partner) = population.first().filter(|_| i != drone_idx) {
+                set_plan(&mut gene, i, &self.individuals[partner].plan);
+            }
+        }
+        set_plan(&mut gene, drone_idx, plan);
+        gene
+    }
+    fn partner_ids(&self, drone_idx: usize) -> Vec<u64> {
+        self.populations
+            .iter()
+            .enumerate()
+            .map(|(i, population)| match population.first() {
+                Some(&partner) if i != drone_idx => self.individuals[partner].id,
+                _ => 0,
+            })
+            .collect()
+    }
+    fn alloc(&mut self) -> usize {
+        self.free.pop().unwrap_or_else(|| {
+            self.individuals.push(Individual::default());
+            self.individuals.len() - 1
+        })
+    }
+    fn release(&mut self, simulation: &mut Simulation, idx: usize) {
+        let individual = &mut self.individuals[idx];
+        for handle in individual.checkpoints.drain(..) {
+            simulation.states.release(handle);
+        }
+        self.free.push(idx);
+    }
+    fn take_population(&mut self, drone_idx: usize) -> Vec<usize> {
+        let spare = std::mem::take(&mut self.spare);
+        std::mem::replace(&mut self.populations[drone_idx], spare)
+    }
+    fn release_population(&mut self, simulation: &mut Simulation, mut population: Vec<usize>) {
+        for idx in population.drain(..) {
+            self.release(simulation, idx);
+        }
+        self.spare = population;
+    }
+    fn score_plan(
+        &mut self,
+        simulation: &mut Simulation,
+        state_proto: &GameState,
+        drone_idx: usize,
+        plan: &DronePlan,
+        parent: Option<usize>,
+    ) -> usize {
+        let mut gene = self.with_partners(drone_idx, plan);
+        let slot = self.alloc();
+        let mut checkpoints = std::mem::take(&mut self.individuals[slot].checkpoints);
+        let (cached, from) = match parent {
+            Some(parent) => {
+                let parent = &self.individuals[parent];
+                let from = parent
+                    .gene
+                    .iter()
+                    .zip(&gene)
+                    .position(|(a, b)| a != b)
+                    .unwrap_or(GENE_SIZE);
+                (&parent.checkpoints[..], from)
+            }
+            None => (&[][..], 0),
+        };
+        simulation.repair_light = planner_rng().gen::<f32>() < self.params.light_repair_rate;
+        let score = self.evaluate(
+            simulation,
+            state_proto,
+            &mut gene,
+            cached,
+            from,
+            &mut checkpoints,
+        );
+        simulation.repair_light = false;
+        self.individuals[slot] = Individual {
+            score,
+            plan: split(&gene, drone_idx),
+            fitness: score.value(),
+            id: 0,
+            gene,
+            checkpoints,
+        };
+        slot
+    }
+    fn add_individual(&mut self, drone_idx: usize, idx: usize) {
+        let individual = &mut self.individuals[idx];
+        if individual.id == 0 {
+            self.next_id += 1;
+            individual.id = self.next_id;
+        }
+        let is_best = match &self.best {
+            Some((best, _)) => individual.fitness > best.value(),
+            None => true,
+        };
+        if is_best {
+            self.best = Some((individual.score, individual.gene));
+        }
+        self.populations[drone_idx].push(idx);
+    }
+    fn add_plan(
+        &mut self,
+        simulation: &mut Simulation,
+        state_proto: &GameState,
+        drone_idx: usize,
+        plan: &DronePlan,
+        parent: Option<usize>,
+    ) {
+        let idx = self.score_plan(simulation, state_proto, drone_idx, plan, parent);
+        self.add_individual(drone_idx, idx);
+    }
+    fn random_opponent_gene(&self) -> Gene {
+        let mut gene = Gene::default();
+        for actions in &mut gene {
+            *actions = self.random_actions();
+        }
+        gene
+    }
+    fn opponent_fitness(
+        &self,
+        simulation: &mut Simulation,
+        state_proto: &GameState,
+        opponent_gene: &Gene,
+    ) -> f32 {
+        let mut gene = match self.best {
+            Some((_, gene)) => gene,
+            None => self.straight_top_gene(),
+        };
+        let mut state = state_proto.clone();
+        simulation.simulate_all_against(&mut state, &mut gene, opponent_gene);
+        -state.score.value()
+    }
+    fn evolve_opponents(&mut self, simulation: &mut Simulation, state_proto: &GameState) {
+        let mut rng = planner_rng();
+        let mut candidates = self
+            .opponents
+            .iter()
+            .map(|(_, gene)| *gene)
+            .collect::<Vec<_>>();
+        for _ in 0..self.params.opponent_population_size {
+            let mut gene = if self.opponents.is_empty() {
+                self.random_opponent_gene()
+            } else {
+                self.opponents[rng.gen_range(0..self.opponents.len())].1
+            };
+            for _ in 0..self.params.mutations_count {
+                let mut_idx = rng.gen_range(0..gene.len());
+                gene[mut_idx] = self.random_actions();
+            }
+            candidates.push(gene);
+        }
+        candidates.push(self.straight_top_gene());
+        let mut opponents = candidates
+            .into_iter()
+            .map(|gene| (self.opponent_fitness(simulation, state_proto, &gene), gene))
+            .collect::<Vec<_>>();
+        opponents.sort_by(|a, b| b.0.total_cmp(&a.0));
+        opponents.truncate(self.params.opponent_population_size);
+        self.opponents = opponents;
+    }
+    fn shift_opponents(&mut self) {
+        for i in 0..self.opponents.len() {
+            let last = self.random_actions();
+            let gene = &mut self.opponents[i].1;
+            gene.rotate_left(1);
+            gene[GENE_SIZE - 1] = last;
+        }
+    }
+    fn random_thrust(&self, rng: &mut impl Rng) -> f32 {
+        match self.params.encoding {
+            GeneEncoding::Angle => 1.,
+            GeneEncoding::Thrust if rng.gen::<f32>() < self.params.wait_rate => 0.,
+            GeneEncoding::Thrust => 1. - rng.gen::<f32>(),
+        }
+    }
+    fn random_action(&self) -> Action {
+        let mut rng = planner_rng();
+        Action {
+            angle: rng.gen_range(-PI..PI),
+            light: rng.gen_bool(0.5),
+            thrust: self.random_thrust(&mut rng),
+        }
+    }
+    fn random_actions(&self) -> Actions {
+        [(); MAX_DRONES].map(|_| self.random_action())
+    }
+    fn random_plan(&self) -> DronePlan {
+        let mut rng = planner_rng();
+        let mut plan = DronePlan::default();
+        for action in &mut plan {
+            action.angle = rng.gen_range(-PI..PI);
+            action.light = false;
+            action.thrust = self.random_thrust(&mut rng);
+        }
+        plan
+    }
+    fn straight_top_plan(&self) -> DronePlan {
+        let mut plan = DronePlan::default();
+        for action in &mut plan {
+            action.angle = -PI / 2.;
+            action.light = false;
+        }
+        plan
+    }
+    fn straight_top_gene(&self) -> Gene {
+        let plan = self.straight_top_plan();
+        let mut gene = Gene::default();
+        for drone_idx in 0..MAX_DRONES {
+            set_plan(&mut gene, drone_idx, &plan);
+        }
+        gene
+    }
+    fn add_straight_top(
+        &mut self,
+        simulation: &mut Simulation,
+        state_proto: &GameState,
+        drone_idx: usize,
+    ) {
+        let plan = self.straight_top_plan();
+        self.add_plan(simulation, state_proto, drone_idx, &plan, None);
+    }
+    fn mutate(&self, plan: &mut DronePlan, rng: &mut impl Rng) {
+        let rates = [
+            self.params.gaussian_rate,
+            self.params.replace_rate,
+            self.params.reverse_rate,
+            self.params.shift_rate,
+            self.params.light_toggle_rate,
+        ];
+        let total = rates.iter().map(|rate| rate.max(0.)).sum::<f32>();
+        let mut pick = rng.gen_range(0. ..total.max(f32::EPSILON));
+        let operator = rates
+            .iter()
+            .position(|rate| {
+                pick -= rate.max(0.);
+                pick < 0.
+            })
+            .unwrap_or(1);
+        let idx = rng.gen_range(0..GENE_SIZE);
+        let (from, to) = {
+            let other = rng.gen_range(0..GENE_SIZE);
+            (idx.min(other), idx.max(other) + 1)
+        };
+        match operator {
+            0 => {
+                let angle = plan[idx].angle + gaussian(rng) * self.params.angle_sigma;
+                plan[idx].angle = (angle + PI).rem_euclid(2. * PI) - PI;
+                if self.params.encoding == GeneEncoding::Thrust {
+                    let thrust = plan[idx].thrust + gaussian(rng) * self.params.thrust_sigma;
+                    plan[idx].thrust = thrust.clamp(0., 1.);
+                }
+            }
+            2 => plan[from..to].reverse(),
+            3 => {
+                if rng.gen_bool(0.5) {
+                    plan[from..to].rotate_left(1);
+                } else {
+                    plan[from..to].rotate_right(1);
+                }
+            }
+            4 => plan[idx].light = !plan[idx].light,
+            _ => plan[idx] = self.random_action(),
+        }
+    }
+    fn mutation(&mut self, simulation: &mut Simulation, state_proto: &GameState, drone_idx: usize) {
+        let mut rng = planner_rng();
+        let population_size = self.populations[drone_idx].len();
+        if population_size == 0 {
+            return;
+        }
+        for _ in 0..self.params.mutations_size {
+            let rank = tournament(population_size, self.params.tournament_size, &mut rng);
+            let idx = self.populations[drone_idx][rank];
+            let mut new_plan = self.individuals[idx].plan;
+            for _ in 0..self.params.mutations_count {
+                self.mutate(&mut new_plan, &mut rng);
+            }
+            self.add_plan(simulation, state_proto, drone_idx, &new_plan, Some(idx));
+        }
+    }
+    fn crossover(
+        &mut self,
+        simulation: &mut Simulation,
+        state_proto: &GameState,
+        drone_idx: usize,
+    ) {
+        let mut rng = planner_rng();
+        let population_size = self.populations[drone_idx].len();
+        if population_size < 2 {
+            return;
+        }
+        for _ in 0..self.params.crossover_size {
+            let rank1 = tournament(population_size, self.params.tournament_size, &mut rng);
+            let mut rank2 = tournament(population_size, self.params.tournament_size, &mut rng);
+            while rank2 == rank1 {
+                rank2 = rng.gen_range(0..population_size);
+            }
+            let idx1 = self.populations[drone_idx][rank1];
+            let idx2 = self.populations[drone_idx][rank2];
+            let plan1 = self.individuals[idx1].plan;
+            let plan2 = self.individuals[idx2].plan;
+            let mut new_plan1 = plan1;
+            let mut new_plan2 = plan2;
+            if rng.gen::<f32>() < self.params.uniform_crossover_rate {
+                for i in 0..GENE_SIZE {
+                    if rng.gen_bool(0.5) {
+                        new_plan1[i] = plan2[i];
+                        new_plan2[i] = plan1[i];
+                    }
+                }
+            } else {
+                let point = rng.gen_range(1..GENE_SIZE);
+                new_plan1[point..].copy_from_slice(&plan2[point..]);
+                new_plan2[point..].copy_from_slice(&plan1[point..]);
+            }
+            self.add_plan(simulation, state_proto, drone_idx, &new_plan1, Some(idx1));
+            self.add_plan(simulation, state_proto, drone_idx, &new_plan2, Some(idx2));
+        }
+    }
+    fn add_randoms(
+        &mut self,
+        simulation: &mut Simulation,
+        state_proto: &GameState,
+        drone_idx: usize,
+    ) {
+        for _ in 0..self.params.random_size {
+            let plan = self.random_plan();
+            self.add_plan(simulation, state_proto, drone_idx, &plan, None);
+        }
+    }
+    fn sort_population(&mut self, drone_idx: usize) {
+        let individuals = &self.individuals;
+        self.populations[drone_idx]
+            .sort_by(|&a, &b| individuals[b].fitness.total_cmp(&individuals[a].fitness));
+    }
+    fn selection(&mut self, simulation: &mut Simulation, drone_idx: usize) {
+        let mut rng = planner_rng();
+        self.sort_population(drone_idx);
+        let mut population = self.take_population(drone_idx);
+        let mut unique = 0;
+        for i in 0..population.len() {
+            let idx = population[i];
+            if unique > 0
+                && self.individuals[population[unique - 1]].plan == self.individuals[idx].plan
+            {
+                self.release(simulation, idx);
+            } else {
+                population[unique] = idx;
+                unique += 1;
+            }
+        }
+        population.truncate(unique);
+        let elite = self
+            .params
+            .elite_size
+            .min(self.params.population_size)
+            .min(population.len());
+        self.populations[drone_idx].extend(population.drain(..elite));
+        while self.populations[drone_idx].len() < self.params.population_size
+            && !population.is_empty()
+        {
+            let rank = tournament(population.len(), self.params.tournament_size, &mut rng);
+            self.populations[drone_idx].push(population.remove(rank));
+        }
+        self.release_population(simulation, population);
+        self.sort_population(drone_idx);
+    }
+    fn diversity(&self, drone_idx: usize) -> f32 {
+        let population = &self.populations[drone_idx];
+        let Some(&best) = population.first() else {
+            return 0.;
+        };
+        let best = &self.individuals[best];
+        let total = population
+            .iter()
+            .flat_map(|&idx| self.individuals[idx].plan.iter().zip(&best.plan))
+            .map(|(a, b)| {
+                let diff = (a.angle - b.angle).rem_euclid(2. * PI);
+                diff.min(2. * PI - diff) / PI
+            })
+            .sum::<f32>();
+        total / (population.len() * GENE_SIZE) as f32
+    }
+    fn refresh(&mut self, simulation: &mut Simulation, state_proto: &GameState, drone_idx: usize) {
+        let partner_ids = self.partner_ids(drone_idx);
+        if self.partners[drone_idx] == partner_ids {
+            return;
+        }
+        self.partners[drone_idx] = partner_ids;
+        let old_population = self.take_population(drone_idx);
+        for &parent in &old_population {
+            let plan = self.individuals[parent].plan;
+            let idx = self.score_plan(simulation, state_proto, drone_idx, &plan, Some(parent));
+            if self.individuals[idx].plan == plan {
+                self.individuals[idx].id = self.individuals[parent].id;
+            }
+            self.add_individual(drone_idx, idx);
+        }
+        self.release_population(simulation, old_population);
+        self.selection(simulation, drone_idx);
+    }
+    fn repair_plan(&self, plan: &mut DronePlan, start: Vec2, tracker: &Tracker) {
+        let mut pos = start;
+        for step in 0..REPAIR_STEPS {
+            pos = (pos + plan[step].get_move()).clamp(Vec2::new(0., 0.), Vec2::new(9999., 9999.));
+            if tracker.is_dangerous(pos) {
+                for action in &mut plan[step..] {
+                    *action = self.random_action();
+                }
+                return;
+            }
+        }
+    }
+    fn warm_start(&mut self, ctx: &PlanningContext, drone_count: usize) {
+        let drone_ids = &ctx.drone_ids[..drone_count];
+        let mut populations = drone_ids
+            .iter()
+            .map(|id| match self.drone_ids.iter().position(|i| i == id) {
+                Some(slot) => std::mem::take(&mut self.populations[slot]),
+                None => Vec::new(),
+            })
+            .collect::<Vec<_>>();
+        for (population, id) in populations.iter_mut().zip(drone_ids) {
+            let drone = ctx.world.me.drones.get(id).filter(|d| d.emergency == 0);
+            let Some(drone) = drone else {
+                self.free.append(population);
+                continue;
+            };
+            for &idx in population.iter() {
+                let mut plan = self.individuals[idx].plan;
+                plan.rotate_left(1);
+                plan[GENE_SIZE - 1] = self.random_action();
+                self.repair_plan(&mut plan, drone.pos, ctx.tracker);
+                self.individuals[idx].plan = plan;
+            }
+        }
+        for mut population in std::mem::replace(&mut self.populations, populations) {
+            self.free.append(&mut population);
+        }
+        self.drone_ids = drone_ids.to_vec();
+    }
+    fn rescore_inherited(
+        &mut self,
+        simulation: &mut Simulation,
+        state_proto: &GameState,
+        drone_idx: usize,
+    ) {
+        let old_population = self.take_population(drone_idx);
+        for &idx in &old_population {
+            let plan = self.individuals[idx].plan;
+            self.add_plan(simulation, state_proto, drone_idx, &plan, None);
+        }
+        self.release_population(simulation, old_population);
+    }
+    pub fn search(&mut self, ctx: &PlanningContext, budget: Duration) -> Plan {
+        let start = Instant::now();
+        let state_proto = GameState::new(ctx);
+        let params = self.params;
+        let mut simulation = Simulation::new(ctx, &params);
+        if self.params.coevolution {
+            self.shift_opponents();
+        }
+        for individual in &mut self.individuals {
+            individual.checkpoints.clear();
+        }
+        let drone_count = state_proto.drone_count;
+        self.warm_start(ctx, drone_count);
+        self.best = None;
+        self.partners = vec![Vec::new(); drone_count];
+        for drone_idx in 0..drone_count {
+            self.rescore_inherited(&mut simulation, &state_proto, drone_idx);
+        }
+        let field = potential_field_gene(ctx, &self.params);
+        for drone_idx in 0..drone_count {
+            self.add_straight_top(&mut simulation, &state_proto, drone_idx);
+            self.add_plan(
+                &mut simulation,
+                &state_proto,
+                drone_idx,
+                &split(&field, drone_idx),
+                None,
+            );
+            for _ in 0..self.params.population_size {
+                let plan = self.random_plan();
+                self.add_plan(&mut simulation, &state_proto, drone_idx, &plan, None);
+            }
+            self.selection(&mut simulation, drone_idx);
+        }
+        let mut iter = 0;
+        while Instant::now().duration_since(start) < budget
+            && (self.params.max_generations == 0 || iter < self.params.max_generations)
+        {
+            iter += 1;
+            for drone_idx in 0..drone_count {
+                self.refresh(&mut simulation, &state_proto, drone_idx);
+                self.add_randoms(&mut simulation, &state_proto, drone_idx);
+                self.mutation(&mut simulation, &state_proto, drone_idx);
+                self.crossover(&mut simulation, &state_proto, drone_idx);
+                self.selection(&mut simulation, drone_idx);
+            }
+            if self.params.coevolution {
+                self.evolve_opponents(&mut simulation, &state_proto);
+            }
+        }
+        let (best_score, best_gene) = self.best.unwrap_or_default();
+        let report = SearchReport {
+            generations: iter,
+            simulations: simulation.total_simulations,
+            dead_simulations: simulation.dead_simulations,
+            reused_steps: simulation.reused_steps,
+            simulated_steps: simulation.simulated_steps,
+            score: best_score,
+            drone_scores: self
+                .populations
+                .iter()
+                .map(|population| {
+                    population
+                        .first()
+                        .map_or_else(Score::default, |&idx| self.individuals[idx].score)
+                })
+                .collect(),
+            diversity: (0..drone_count).map(|i| self.diversity(i)).collect(),
+            elapsed: start.elapsed(),
+        };
+        Plan {
+            actions: best_gene[0],
+            gene: best_gene,
+            score: best_score,
+            report,
+        }
+    }
+}
+impl Planner for Pathfinding {
+    fn plan(&mut self, ctx: &PlanningContext, budget: Duration) -> Plan {
+        self.search(ctx, budget)
+    }
+    fn candidates(&self) -> Vec<Gene> {
+        let ranks = self.populations.iter().map(Vec::len).min().unwrap_or(0);
+        let paired = (0..ranks).map(|rank| {
+            let mut gene = Gene::default();
+            for (drone_idx, population) in self.populations.iter().enumerate() {
+                set_plan(
+                    &mut gene,
+                    drone_idx,
+                    &self.individuals[population[rank]].plan,
+                );
+            }
+            gene
+        });
+        self.best
+            .iter()
+            .map(|(_, gene)| *gene)
+            .chain(paired)
+            .collect()
+    }
+}
+}
+pub mod planner {
+use std::{fmt::Display, time::Duration};
+use super::*;
+pub struct PlanningContext<'a> {
+    pub world: &'a World,
+    pub tracker: &'a Tracker,
+    pub bounds_detector: &'a BoundsDetector,
+    pub exploration_map: &'a ExplorationMap,
+    pub score_map: &'a ScoreMap,
+    pub drone_score_maps: &'a [ScoreMap],
+    pub battery: &'a BatteryPlan,
+    pub drone_ids: &'a [i32],
+}
+#[derive(Clone, Default, Debug)]
+pub struct SearchReport {
+    pub generations: usize,
+    pub simulations: i32,
+    pub dead_simulations: i32,
+    pub reused_steps: i32,
+    pub simulated_steps: i32,
+    pub score: Score,
+    pub drone_scores: Vec<Score>,
+    pub diversity: Vec<f32>,
+    pub elapsed: Duration,
+}
+impl SearchReport {
+    pub fn simulations_per_ms(&self) -> f32 {
+        self.simulations as f32 / self.elapsed_ms()
+    }
+    pub fn uncached_per_ms(&self) -> f32 {
+        self.simulated_steps as f32 / GENE_SIZE as f32 / self.elapsed_ms()
+    }
+    fn elapsed_ms(&self) -> f32 {
+        (self.elapsed.as_secs_f32() * 1000.).max(f32::EPSILON)
+    }
+}
+impl Display for SearchReport {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let diversity = self
+            .diversity
+            .iter()
+            .map(|d| format!("{d:.2}"))
+            .collect::<Vec<_>>();
+        write!(
+            f,
+            "gen {} sims {} dead {} ({:.0}/ms, {:.0}/ms uncached) score {:.2} diversity [{}] {}ms",
+            self.generations,
+            self.simulations,
+            self.dead_simulations,
+            self.simulations_per_ms(),
+            self.uncached_per_ms(),
+            self.score.value(),
+            diversity.join(" "),
+            self.elapsed.as_millis()
+        )
+    }
+}
+#[derive(Clone, Default, Debug)]
+pub struct GameReport {
+    pub turns: usize,
+    pub simulations: i64,
+    pub dead_simulations: i64,
+    pub simulated_steps: i64,
+    pub elapsed: Duration,
+    pub max_elapsed: Duration,
+}
+impl GameReport {
+    pub fn add(&mut self, report: &SearchReport) {
+        self.turns += 1;
+        self.simulations += report.simulations as i64;
+        self.dead_simulations += report.dead_simulations as i64;
+        self.simulated_steps += report.simulated_steps as i64;
+        self.elapsed += report.elapsed;
+        self.max_elapsed = self.max_elapsed.max(report.elapsed);
+    }
+    fn elapsed_ms(&self) -> f32 {
+        (self.elapsed.as_secs_f32() * 1000.).max(f32::EPSILON)
+    }
+}
+impl Display for GameReport {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "game: turns {} sims {} dead {} ({:.0}/ms, {:.0}/ms uncached) max {}ms",
+            self.turns,
+            self.simulations,
+            self.dead_simulations,
+            self.simulations as f32 / self.elapsed_ms(),
+            self.simulated_steps as f32 / GENE_SIZE as f32 / self.elapsed_ms(),
+            self.max_elapsed.as_millis()
+        )
+    }
+}
+pub struct Plan {
+    pub actions: Actions,
+    pub gene: Gene,
+    pub score: Score,
+    pub report: SearchReport,
+}
+pub trait Planner {
+    fn plan(&mut self, ctx: &PlanningContext, budget: Duration) -> Plan;
+    fn candidates(&self) -> Vec<Gene> {
+        Vec::new()
+    }
+}
+pub fn new_planner(params: StrategyParams) -> Box<dyn Planner> {
+    match params.planner {
+        PlannerKind::Genetic => Box::new(Pathfinding::new(params)),
+        PlannerKind::Beam => Box::new(BeamSearch::new(params)),
+        PlannerKind::Mcts => Box::new(Mcts::new(params)),
+    }
+}
+pub fn score_gene(ctx: &PlanningContext, params: &StrategyParams, gene: &Gene) -> Score {
+    let mut simulation = Simulation::new(ctx, params);
+    let mut state = GameState::new(ctx);
+    let mut gene = *gene;
+    simulation.simulate_all(&mut state, &mut gene);
+    state.score
+}
+}
+pub mod random {
+use std::cell::RefCell;
+use rand::{rngs::StdRng, RngCore, SeedableRng};
+thread_local! {
+    static RNG: RefCell<StdRng> = RefCell::new(StdRng::from_entropy());
+}
+pub fn seed_rng(seed: u64) {
+    RNG.with(|rng| *rng.borrow_mut() = StdRng::seed_from_u64(seed));
+}
+#[derive(Clone, Copy)]
+pub struct PlannerRng;
+pub fn planner_rng() -> PlannerRng {
+    PlannerRng
+}
+impl RngCore for PlannerRng {
+    fn next_u32(&mut self) -> u32 {
+        RNG.with(|rng| rng.borrow_mut().next_u32())
+    }
+    fn next_u64(&mut self) -> u64 {
+        RNG.with(|rng| rng.borrow_mut().next_u64())
     }
-    pub fn get_fish_cost(&self, id: i32) -> f32 {
-        *self.fish_cost.get(&id).unwrap()
+    fn fill_bytes(&mut self, dest: &mut [u8]) {
+        RNG.with(|rng| rng.borrow_mut().fill_bytes(dest))
+    }
+    fn try_fill_bytes(&mut self, dest: &mut [u8]) -> Result<(), rand::Error> {
+        RNG.with(|rng| rng.borrow_mut().try_fill_bytes(dest))
     }
 }
 }
-pub mod pathfinding {
-use std::{f32::consts::PI, time::Instant};
+pub mod simulation {
+use std::f32::consts::PI;
 use rand::Rng;
 use super::*;
-#[derive(Default, Clone, Copy)]
+#[derive(Clone, Copy, PartialEq)]
 pub struct Action {
-    angle: f32,
-    light: bool,
+    pub(crate) angle: f32,
+    pub(crate) light: bool,
+    pub(crate) thrust: f32,
+}
+impl Default for Action {
+    fn default() -> Self {
+        Action {
+            angle: 0.,
+            light: false,
+            thrust: 1.,
+        }
+    }
 }
+pub(crate) const DRONE_SPEED: f32 = 600.;
+const SINK_SPEED: f32 = 300.;
 impl Action {
     pub fn get_light(&self) -> bool {
         self.light
     }
+    pub fn is_wait(&self) -> bool {
+        self.thrust <= 0.
+    }
     pub fn get_move(&self) -> Vec2 {
-        Vec2::new(1., 0.).rotate(self.angle) * 600.
+        if self.is_wait() {
+            Vec2::new(0., SINK_SPEED)
+        } else {
+            Vec2::new(1., 0.).rotate(self.angle) * DRONE_SPEED * self.thrust.min(1.)
+        }
+    }
+    pub(crate) fn discrete(angles: usize, encoding: GeneEncoding) -> Vec<Action> {
+        let thrusts: &[f32] = match encoding {
+            GeneEncoding::Angle => &[1.],
+            GeneEncoding::Thrust => &[1., 0.5],
+        };
+        let moves = (0..angles.max(1)).flat_map(|i| {
+            let angle = -PI + 2. * PI * i as f32 / angles.max(1) as f32;
+            thrusts.iter().map(move |&thrust| (angle, thrust))
+        });
+        let wait = (encoding == GeneEncoding::Thrust).then_some((0., 0.));
+        moves
+            .chain(wait)
+            .flat_map(|(angle, thrust)| {
+                [false, true].map(|light| Action {
+                    angle,
+                    light,
+                    thrust,
+                })
+            })
+            .collect()
     }
 }
 #[derive(Default, Clone, Copy)]
-struct DroneState {
+pub(crate) struct DroneState {
     pos: Vec2,
     bat: i32,
-    dead: bool,
+    emergency_turns: i32,
+    caught: bool,
     base_scans_cost: i32,
     urgent_scans_cost: i32,
+    race_scans: u64,
+    missing: bool,
+    band_lights: [u8; DEPTH_BANDS],
 }
-#[derive(Clone, Copy, Default)]
+#[derive(Clone, Copy, Default, Debug)]
 pub struct Score {
-    saving_scans_score: f32,
-    saving_urgent_scans_score: f32,
-    exploration_score: f32,
-    ascent_score: f32,
-    dive_score: f32,
-    dead_score: f32,
+    pub saving_scans_score: f32,
+    pub saving_urgent_scans_score: f32,
+    pub exploration_score: f32,
+    pub scan_risk_score: f32,
+    pub dive_score: f32,
+    pub dead_score: f32,
+    pub lost_scans_score: f32,
+    pub race_score: f32,
+    pub aggression_score: f32,
+    pub overlap_score: f32,
+    pub combo_score: f32,
 }
 impl Score {
-    fn value(&self) -> f32 {
+    pub fn value(&self) -> f32 {
         self.saving_scans_score
             + self.saving_urgent_scans_score
             + self.exploration_score
             + self.dead_score
-            + self.ascent_score
+            + self.lost_scans_score
+            + self.scan_risk_score
             + self.dive_score
+            + self.race_score
+            + self.aggression_score
+            + self.overlap_score
+            + self.combo_score
+    }
+}
+const CELL_WORDS: usize = (S_CELLS * S_CELLS).div_ceil(64);
+#[derive(Clone, Copy, Default)]
+struct CellSet([u64; CELL_WORDS]);
+impl CellSet {
+    fn contains(&self, x: usize, y: usize) -> bool {
+        let bit = x * S_CELLS + y;
+        self.0[bit / 64] & 1 << (bit % 64) != 0
+    }
+    fn insert(&mut self, x: usize, y: usize) {
+        let bit = x * S_CELLS + y;
+        self.0[bit / 64] |= 1 << (bit % 64);
     }
 }
 #[derive(Clone)]
-struct GameState {
-    drones: [DroneState; 2],
-    visited: [[bool; S_CELLS]; S_CELLS],
-    score: Score,
+pub(crate) struct GameState {
+    pub(crate) drones: [DroneState; MAX_DRONES],
+    pub(crate) drone_count: usize,
+    opponents: [DroneState; MAX_DRONES],
+    opponent_count: usize,
+    fish_costs: [u8; MAX_CREATURE_ID],
+    visited: [CellSet; MAX_DRONES],
+    scanned: u64,
+    surfaced: u8,
+    combos_done: u32,
+    pub(crate) score: Score,
     iter: i32,
 }
+const EMERGENCY_SPEED: f32 = 300.;
+const MAX_CREATURE_ID: usize = 64;
+pub(crate) const LIGHT_COST: i32 = 5;
+const MAX_BATTERY: i32 = 30;
+pub(crate) const LIGHT_RADIUS: f32 = 800.;
+pub(crate) const POWERED_LIGHT_RADIUS: f32 = 2000.;
+fn emergency_turns(pos: Vec2) -> i32 {
+    ((pos.y / EMERGENCY_SPEED).ceil() as i32).max(1)
+}
 fn estimate_drones_scans_profit(world: &World, drone: &Drone, state: &mut DroneState) {
     let mut by_typ_count = [0; 3];
     let mut by_color_count = [0; 4];
@@ -366,215 +3115,203 @@ fn estimate_drones_scans_profit(world: &World, drone: &Drone, state: &mut DroneS
         }
     }
 }
+fn race_scans(world: &World, drone: &Drone) -> u64 {
+    drone
+        .scans
+        .iter()
+        .filter(|&&id| (id as usize) < MAX_CREATURE_ID)
+        .filter(|id| !world.me.scans.contains(id) && !world.opponent.scans.contains(id))
+        .fold(0, |mask, &id| mask | 1 << id)
+}
 impl GameState {
-    fn new(world: &World) -> Self {
-        let mut drones = [DroneState::default(); 2];
-        for (i, drone) in world.me.drones.values().enumerate() {
-            drones[i].pos = drone.pos;
-            drones[i].bat = drone.bat;
-            drones[i].dead = drone.emergency == 1;
-            estimate_drones_scans_profit(world, drone, &mut drones[i]);
-        }
-        let visited = [[false; S_CELLS]; 20];
+    pub(crate) fn new(ctx: &PlanningContext) -> Self {
+        let world = ctx.world;
+        let mut drones = [DroneState::default(); MAX_DRONES];
+        for (state, id) in drones.iter_mut().zip(ctx.drone_ids) {
+            let Some(drone) = world.me.drones.get(id) else {
+                state.missing = true;
+                continue;
+            };
+            state.pos = drone.pos;
+            state.bat = drone.bat;
+            if drone.emergency == 1 {
+                state.emergency_turns = emergency_turns(drone.pos);
+            }
+            estimate_drones_scans_profit(world, drone, state);
+            state.race_scans = race_scans(world, drone);
+        }
+        let mut opponents = [DroneState::default(); MAX_DRONES];
+        for (i, drone) in world.opponent.drones.values().take(MAX_DRONES).enumerate() {
+            opponents[i].pos = drone.pos;
+            opponents[i].bat = drone.bat;
+            if drone.emergency == 1 {
+                opponents[i].emergency_turns = emergency_turns(drone.pos);
+            }
+            opponents[i].race_scans = race_scans(world, drone);
+        }
+        let mut fish_costs = [0; MAX_CREATURE_ID];
+        for c in world.creatures.values() {
+            if c.typ != -1 && (c.id as usize) < MAX_CREATURE_ID {
+                fish_costs[c.id as usize] = (c.typ + 1) as u8;
+            }
+        }
         GameState {
             drones,
-            visited,
+            drone_count: ctx.drone_ids.len().min(MAX_DRONES),
+            opponents,
+            opponent_count: world.opponent.drones.len().min(MAX_DRONES),
+            fish_costs,
+            visited: [CellSet::default(); MAX_DRONES],
+            scanned: 0,
+            surfaced: 0,
+            combos_done: 0,
             score: Score::default(),
             iter: world.iter,
         }
     }
-    fn visit_score(&self, x: usize, y: usize) -> f32 {
-        if !self.visited[x][y] {
+    fn any_caught(&self) -> bool {
+        self.drones[..self.drone_count].iter().any(|d| d.caught)
+    }
+    fn visit_score(&self, x: usize, y: usize, params: &StrategyParams) -> f32 {
+        if !self.visited.iter().any(|cells| cells.contains(x, y)) {
             1.
         } else {
-            1. / 100.
+            params.visited_score
         }
     }
-    fn visit_cell(&mut self, x: usize, y: usize) {
-        self.visited[x][y] = true;
+    fn visit_cell(&mut self, x: usize, y: usize, drone_idx: usize) {
+        self.visited[drone_idx].insert(x, y);
+    }
+    fn visited_by_partner(&self, x: usize, y: usize, drone_idx: usize) -> bool {
+        self.visited
+            .iter()
+            .enumerate()
+            .any(|(i, cells)| i != drone_idx && cells.contains(x, y))
     }
 }
-const GENE_SIZE: usize = 25;
-const POPULATION_SIZE: usize = 30;
-const MUTATIONS_SIZE: usize = 30;
-const MUTATIONS_COUNT: usize = 3;
-const RANDOM_SIZE: usize = 10;
-const CROSSOVER_SIZE: usize = 30;
-type Gene = [[Action; 2]; GENE_SIZE];
-struct Simulation<'a> {
-    tracker: &'a Tracker,
-    exploration_map: &'a ExplorationMap,
-    score_map: &'a ScoreMap,
-    dead_simulations: i32,
-    total_simulations: i32,
+fn scans_cost(fish_costs: &[u8; MAX_CREATURE_ID], mask: u64) -> i32 {
+    (0..MAX_CREATURE_ID)
+        .filter(|id| mask & 1 << id != 0)
+        .map(|id| fish_costs[id] as i32)
+        .sum()
 }
-pub struct Pathfinding {
-    pub population: Vec<(Score, Gene)>,
+pub const GENE_SIZE: usize = 25;
+pub const MAX_DRONES: usize = 2;
+pub type Actions = [Action; MAX_DRONES];
+pub type Gene = [Actions; GENE_SIZE];
+struct SimulatedFish {
+    id: usize,
+    pos: Vec2,
+    cost: i32,
+    urgent: bool,
 }
-impl Pathfinding {
-    pub fn new() -> Self {
-        Pathfinding {
-            population: Vec::new(),
-        }
-    }
-    fn random_actions(&self) -> [Action; 2] {
-        let mut rng = rand::thread_rng();
-        let mut actions: [Action; 2] = Default::default();
-        for action in &mut actions {
-            action.light = rng.gen_bool(0.5);
-            action.angle = rng.gen_range(-PI..PI);
-        }
-        actions
-    }
-    fn random_gene(&self) -> Gene {
-        let mut rng = rand::thread_rng();
-        let mut gene = Gene::default();
-        for actions in &mut gene {
-            for action in actions {
-                action.angle = rng.gen_range(-PI..PI);
-                action.light = false;
-            }
-        }
-        gene
-    }
-    fn straight_top_gene(&self) -> Gene {
-        let mut gene = Gene::default();
-        for actions in &mut gene {
-            for action in actions {
-                action.angle = -PI / 2.;
-                action.light = false;
+fn sample_fish(world: &World, bounds_detector: &BoundsDetector) -> Vec<SimulatedFish> {
+    let mut rng = planner_rng();
+    world
+        .creatures
+        .values()
+        .filter(|c| c.typ != -1 && (c.id as usize) < MAX_CREATURE_ID)
+        .filter(|c| !world.me.scans.contains(&c.id))
+        .filter(|c| {
+            world
+                .me
+                .drones
+                .values()
+                .all(|drone| !drone.scans.contains(&c.id))
+        })
+        .filter(|c| {
+            world
+                .me
+                .drones
+                .values()
+                .any(|drone| drone.blips.contains_key(&c.id))
+        })
+        .map(|c| {
+            let pos = c.pos.unwrap_or_else(|| {
+                let bounds = bounds_detector.get_bounds(c.id);
+                let (min, max) = (bounds.top_left, bounds.bot_right);
+                Vec2::new(
+                    min.x + (max.x - min.x) * rng.gen::<f32>(),
+                    min.y + (max.y - min.y) * rng.gen::<f32>(),
+                )
+            });
+            SimulatedFish {
+                id: c.id as usize,
+                pos,
+                cost: (c.typ + 1) as i32,
+                urgent: !world.opponent.scans.contains(&c.id),
             }
-        }
-        gene
-    }
-    fn add_straight_top(&mut self, simulation: &mut Simulation, state_proto: &GameState) {
-        let mut gene = self.straight_top_gene();
-        let mut state = state_proto.clone();
-        simulation.simulate_all(&mut state, &mut gene);
-        self.population.push((state.score, gene));
-    }
-    fn mutation(&mut self, simulation: &mut Simulation, state_proto: &GameState) {
-        let mut rng = rand::thread_rng();
-        let population_size = self.population.len();
-        for _ in 0..MUTATIONS_SIZE {
-            let idx = rng.gen_range(0..population_size);
-            let (_, mut new_gene) = self.population[idx].clone();
-            for _ in 0..MUTATIONS_COUNT {
-                let mut_idx = rng.gen_range(0..new_gene.len());
-                new_gene[mut_idx] = self.random_actions();
+        })
+        .collect()
+}
+pub(crate) type StateHandle = u32;
+#[derive(Default)]
+pub(crate) struct StatePool {
+    states: Vec<GameState>,
+    refs: Vec<u32>,
+    free: Vec<StateHandle>,
+}
+impl StatePool {
+    fn insert(&mut self, state: &GameState) -> StateHandle {
+        match self.free.pop() {
+            Some(handle) => {
+                self.states[handle as usize] = state.clone();
+                self.refs[handle as usize] = 1;
+                handle
             }
-            let mut state = state_proto.clone();
-            simulation.simulate_all(&mut state, &mut new_gene);
-            self.population.push((state.score, new_gene));
-        }
-    }
-    fn crossover(&mut self, simulation: &mut Simulation, state_proto: &GameState) {
-        let mut rng = rand::thread_rng();
-        let population_size = self.population.len();
-        for _ in 0..CROSSOVER_SIZE {
-            let idx1 = rng.gen_range(0..population_size);
-            let mut idx2 = rng.gen_range(0..population_size);
-            while idx2 == idx1 {
-                idx2 = rng.gen_range(0..population_size);
-            }
-            let (_, gene1) = &self.population[idx1];
-            let (_, gene2) = &self.population[idx2];
-            let mut new_gene1 = gene1.clone();
-            let mut new_gene2 = gene2.clone();
-            for i in 0..gene1.len() {
-                new_gene1[i][1] = gene2[i][1];
-                new_gene2[i][1] = gene1[i][1];
+            None => {
+                self.states.push(state.clone());
+                self.refs.push(1);
+                (self.states.len() - 1) as StateHandle
             }
-            let mut state = state_proto.clone();
-            simulation.simulate_all(&mut state, &mut new_gene1);
-            self.population.push((state.score, new_gene1));
-            let mut state = state_proto.clone();
-            simulation.simulate_all(&mut state, &mut new_gene2);
-            self.population.push((state.score, new_gene2));
-        }
-    }
-    fn add_randoms(&mut self, simulation: &mut Simulation, state_proto: &GameState) {
-        for _ in 0..RANDOM_SIZE {
-            let mut gene = self.random_gene();
-            let mut state = state_proto.clone();
-            simulation.simulate_all(&mut state, &mut gene);
-            self.population.push((state.score, gene));
         }
     }
-    fn selection(&mut self) {
-        self.population
-            .sort_by_key(|(score, _)| -(score.value() * 100.) as i32);
-        self.population.truncate(POPULATION_SIZE);
+    fn get(&self, handle: StateHandle) -> &GameState {
+        &self.states[handle as usize]
     }
-    fn modify_prev_generation(&mut self, simulation: &mut Simulation, state_proto: &GameState) {
-        let mut old_population = self.population.clone();
-        self.population.clear();
-        for (_, action) in &mut old_population {
-            let mut gene = Gene::default();
-            for i in 1..action.len() {
-                gene[i - 1] = action[i];
-            }
-            gene[gene.len() - 1] = self.random_actions();
-            let mut state = state_proto.clone();
-            simulation.simulate_all(&mut state, &mut gene);
-            self.population.push((state.score, gene));
-        }
+    fn retain(&mut self, handle: StateHandle) {
+        self.refs[handle as usize] += 1;
     }
-    pub fn search(
-        &mut self,
-        world: &World,
-        tracker: &Tracker,
-        exploration_map: &ExplorationMap,
-        score_map: &ScoreMap,
-    ) -> [Action; 2] {
-        let start = Instant::now();
-        let state_proto = GameState::new(world);
-        let mut simulation = Simulation::new(tracker, exploration_map, score_map);
-        self.modify_prev_generation(&mut simulation, &state_proto);
-        self.add_straight_top(&mut simulation, &state_proto);
-        for _ in 0..POPULATION_SIZE {
-            let mut gene = self.random_gene();
-            let mut state = state_proto.clone();
-            simulation.simulate_all(&mut state, &mut gene);
-            self.population.push((state.score, gene));
+    pub(crate) fn release(&mut self, handle: StateHandle) {
+        self.refs[handle as usize] -= 1;
+        if self.refs[handle as usize] == 0 {
+            self.free.push(handle);
         }
-        let mut iter = 0;
-        while Instant::now().duration_since(start).as_millis() < 40 {
-            iter += 1;
-            self.add_randoms(&mut simulation, &state_proto);
-            self.mutation(&mut simulation, &state_proto);
-            self.crossover(&mut simulation, &state_proto);
-            self.selection();
-        }
-        let (best_score, best_actions) = self.population[0];
-        eprintln!("Iterations count: {iter}");
-        eprintln!("Best score: {}", best_score.value());
-        eprintln!("Exploration score: {}", best_score.exploration_score);
-        eprintln!("Saving scans score: {}", best_score.saving_scans_score);
-        eprintln!(
-            "Saving urgent scans score: {}",
-            best_score.saving_urgent_scans_score
-        );
-        eprintln!("Ascent score: {}", best_score.ascent_score);
-        eprintln!("Dive score: {}", best_score.dive_score);
-        eprintln!("Dead score: {}", best_score.dead_score);
-        eprintln!("Dead simulations: {}", simulation.dead_simulations);
-        eprintln!("Total simulations: {}", simulation.total_simulations);
-        best_actions[0]
     }
 }
+pub(crate) struct Simulation<'a> {
+    tracker: &'a Tracker,
+    exploration: ScoreGrid,
+    score_map: &'a ScoreMap,
+    drone_score_maps: &'a [ScoreMap],
+    battery: &'a BatteryPlan,
+    params: &'a StrategyParams,
+    fish: Vec<SimulatedFish>,
+    combo_goals: Vec<ComboGoal>,
+    pub(crate) dead_simulations: i32,
+    pub(crate) total_simulations: i32,
+    pub(crate) reused_steps: i32,
+    pub(crate) simulated_steps: i32,
+    pub(crate) repair_light: bool,
+    pub(crate) states: StatePool,
+}
 impl<'a> Simulation<'a> {
-    pub fn new(
-        tracker: &'a Tracker,
-        exploration_map: &'a ExplorationMap,
-        score_map: &'a ScoreMap,
-    ) -> Self {
+    pub(crate) fn new(ctx: &PlanningContext<'a>, params: &'a StrategyParams) -> Self {
         Simulation {
-            tracker,
-            exploration_map,
-            score_map,
+            tracker: ctx.tracker,
+            exploration: ctx.exploration_map.on_score_grid(),
+            score_map: ctx.score_map,
+            drone_score_maps: ctx.drone_score_maps,
+            battery: ctx.battery,
+            params,
+            fish: sample_fish(ctx.world, ctx.bounds_detector),
+            combo_goals: combo_goals(ctx.world, ctx.drone_ids),
             dead_simulations: 0,
             total_simulations: 0,
+            reused_steps: 0,
+            simulated_steps: 0,
+            repair_light: false,
+            states: StatePool::default(),
         }
     }
     fn choose_dead_move(&self, state: &mut GameState, drone_idx: usize, action: &mut Action) {
@@ -613,7 +3350,12 @@ impl<'a> Simulation<'a> {
         action.angle += rot;
         state.score.dead_score += (dist / 2) as f32;
     }
-    fn adjust_drone_move(&self, state: &mut GameState, drone_idx: usize, action: &mut Action) {
+    fn adjust_drone_move(
+        &self,
+        state: &mut GameState,
+        drone_idx: usize,
+        action: &mut Action,
+    ) -> bool {
         let drone = &mut state.drones[drone_idx];
         let base_angle = action.angle;
         for rotation in [
@@ -624,47 +3366,110 @@ impl<'a> Simulation<'a> {
             -PI,
             -PI, // for last iteration happen
         ] {
-            let dir = Vec2::new(1., 0.).rotate(action.angle);
-            let mov = dir * 600.;
-            let new_pos = (drone.pos + mov).clamp(Vec2::new(0., 0.), Vec2::new(9999., 9999.));
-            drone.dead = self
-                .tracker
-                .monsters
-                .iter()
-                .any(|m| (m.pos - new_pos).len() < 1200.);
-            if !drone.dead {
-                return;
+            let new_pos =
+                (drone.pos + action.get_move()).clamp(Vec2::new(0., 0.), Vec2::new(9999., 9999.));
+            if !self.tracker.is_dangerous(new_pos) {
+                return true;
             }
+            action.thrust = 1.;
             action.angle = base_angle + rotation;
             if action.angle > PI {
                 action.angle -= 2. * PI;
             }
         }
         self.choose_dead_move(state, drone_idx, action);
+        false
+    }
+    fn enter_emergency(&self, state: &mut GameState, drone_idx: usize, iter: usize) {
+        let drone = &mut state.drones[drone_idx];
+        drone.caught = true;
+        drone.emergency_turns = emergency_turns(drone.pos);
+        state.score.lost_scans_score -=
+            drone.base_scans_cost as f32 * self.params.lost_scans_weight;
+        state.score.dead_score -= self.params.dead_penalty * (GENE_SIZE + 1 - iter) as f32;
+        drone.base_scans_cost = 0;
+        drone.urgent_scans_cost = 0;
+    }
+    fn float_up(drone: &mut DroneState) {
+        drone.pos.y = (drone.pos.y - EMERGENCY_SPEED).max(0.);
+        drone.emergency_turns -= 1;
+    }
+    fn scan_fish(&self, state: &mut GameState, drone_idx: usize, light: bool) {
+        let radius = if light {
+            POWERED_LIGHT_RADIUS
+        } else {
+            LIGHT_RADIUS
+        };
+        let drone = &mut state.drones[drone_idx];
+        for fish in &self.fish {
+            if state.scanned & 1 << fish.id != 0 || (fish.pos - drone.pos).len() > radius {
+                continue;
+            }
+            state.scanned |= 1 << fish.id;
+            drone.base_scans_cost += fish.cost;
+            if fish.urgent {
+                drone.urgent_scans_cost += fish.cost;
+                drone.race_scans |= 1 << fish.id;
+            }
+        }
+    }
+    fn score_map(&self, drone_idx: usize) -> &ScoreMap {
+        self.drone_score_maps
+            .get(drone_idx)
+            .unwrap_or(self.score_map)
+    }
+    fn light_budgeted(&self, drone: &DroneState, drone_idx: usize, band: usize) -> bool {
+        !self.params.battery_planner
+            || (drone.band_lights[band] as usize) < self.battery.lights(drone_idx, band)
+    }
+    fn light_score(&self, state: &GameState, drone_idx: usize, pos: Vec2) -> f32 {
+        let score_map = self.score_map(drone_idx);
+        ScoreGrid::circle(pos, POWERED_LIGHT_RADIUS)
+            .map(|(x, y)| {
+                self.exploration[(x, y)]
+                    * score_map.get_score_by_idx(x, y)
+                    * state.visit_score(x, y, self.params)
+            })
+            .sum()
     }
-    fn simulate(&mut self, state: &mut GameState, actions: &mut [Action; 2], iter: usize) {
-        for i in 0..2 {
-            let action = &mut actions[i];
-            if state.drones[i].dead {
-                state.score.dead_score -= 1000.;
+    fn monster_danger(&self, pos: Vec2, iter: usize) -> f32 {
+        self.tracker
+            .monsters
+            .iter()
+            .map(|m| (1. - (m.pos_at(iter) - pos).len() / self.params.danger_radius).max(0.))
+            .sum()
+    }
+    fn scans_at_risk(&self, drone: &DroneState, iter: usize) -> f32 {
+        let depth = drone.pos.y / 10000.;
+        let danger = self.params.depth_risk + self.monster_danger(drone.pos, iter);
+        drone.base_scans_cost as f32 * depth * danger * self.params.scan_risk_weight
+    }
+    pub(crate) fn simulate(&mut self, state: &mut GameState, actions: &mut Actions, iter: usize) {
+        self.simulated_steps += 1;
+        for (i, action) in actions.iter_mut().enumerate().take(state.drone_count) {
+            if state.drones[i].missing {
+                continue;
+            }
+            if state.drones[i].emergency_turns > 0 {
+                Self::float_up(&mut state.drones[i]);
                 continue;
             }
-            self.adjust_drone_move(state, i, action);
-            if state.drones[i].dead {
-                state.score.dead_score -= 1000.;
+            if !self.adjust_drone_move(state, i, action) {
+                self.enter_emergency(state, i, iter);
                 continue;
             }
-            let dir = Vec2::new(1., 0.).rotate(action.angle);
-            let mov = dir * 300.;
+            let mov = action.get_move() * 0.5;
             for _ in 0..2 {
                 let drone = &mut state.drones[i];
                 drone.pos = (drone.pos + mov).clamp(Vec2::new(0., 0.), Vec2::new(9999., 9999.));
-                let (x, y) = position_to_grid_cell(drone.pos, S_CELL_SIZE);
-                state.score.exploration_score += self.exploration_map.get_score_by_idx(x, y)
-                    * self.score_map.get_score_by_idx(x, y)
-                    * state.visit_score(x, y)
-                    / (iter as f32);
-                state.visit_cell(x, y);
+                let (x, y) = ScoreGrid::cell(drone.pos);
+                let value = self.exploration[(x, y)] * self.score_map(i).get_score_by_idx(x, y);
+                state.score.exploration_score +=
+                    value * state.visit_score(x, y, self.params) / (iter as f32);
+                if state.visited_by_partner(x, y, i) {
+                    state.score.overlap_score -= value * self.params.overlap_weight / (iter as f32);
+                }
+                state.visit_cell(x, y, i);
             }
             let drone = &mut state.drones[i];
             if drone.pos.y < 400. {
@@ -674,119 +3479,426 @@ impl<'a> Simulation<'a> {
                     (drone.urgent_scans_cost * drone.urgent_scans_cost) as f32 / iter / iter;
                 drone.base_scans_cost = 0;
                 drone.urgent_scans_cost = 0;
-            }
-            let drone = &state.drones[i];
-            let (x, y) = position_to_grid_cell(drone.pos, S_CELL_SIZE);
-            let (x, y) = (x as i32, y as i32);
-            let mut light_score = 0.;
-            if drone.bat >= 5 {
-                for dx in -1..2 {
-                    for dy in -1..2 {
-                        if x + dx >= 0
-                            && x + dx < S_CELLS as i32
-                            && y + dy >= 0
-                            && y + dy < S_CELLS as i32
-                        {
-                            let (x, y) = ((x + dx) as usize, (y + dy) as usize);
-                            light_score += self.exploration_map.get_score_by_idx(x, y)
-                                * self.score_map.get_score_by_idx(x, y)
-                                * state.visit_score(x, y);
-                        }
-                    }
-                }
-                if light_score > 0.5 {
-                    action.light = true;
-                } else {
-                    action.light = false;
+                state.surfaced |= 1 << i;
+                let saved = drone.race_scans;
+                for drone in &mut state.drones {
+                    drone.race_scans &= !saved;
                 }
+            }
+            let (pos, bat) = (state.drones[i].pos, state.drones[i].bat);
+            let band = depth_band(pos.y);
+            let can_light = bat >= LIGHT_COST && self.light_budgeted(&state.drones[i], i, band);
+            let light_score = if can_light && (action.light || self.repair_light) {
+                self.light_score(state, i, pos)
             } else {
+                0.
+            };
+            if !can_light {
                 action.light = false;
+            } else if self.repair_light {
+                action.light = light_score > self.params.light_threshold;
             }
             if action.light {
-                for dx in -1..2 {
-                    for dy in -1..2 {
-                        if x + dx >= 0
-                            && x + dx < S_CELLS as i32
-                            && y + dy >= 0
-                            && y + dy < S_CELLS as i32
-                        {
-                            let (x, y) = ((x + dx) as usize, (y + dy) as usize);
-                            state.visit_cell(x, y);
-                        }
-                    }
+                for (x, y) in ScoreGrid::circle(pos, POWERED_LIGHT_RADIUS) {
+                    state.visit_cell(x, y, i);
                 }
                 state.score.exploration_score += light_score / (iter as f32);
             }
+            self.scan_fish(state, i, action.light);
             let drone = &mut state.drones[i];
             if action.light {
-                drone.bat -= 5;
+                drone.bat -= LIGHT_COST;
+                drone.band_lights[band] += 1;
             } else {
-                drone.bat += 1;
+                drone.bat = (drone.bat + 1).min(MAX_BATTERY);
             }
-            state.score.ascent_score +=
-                drone.base_scans_cost as f32 * (1. - drone.pos.y / 10000.) / 5. / GENE_SIZE as f32;
+            state.score.scan_risk_score -= self.scans_at_risk(drone, iter) / GENE_SIZE as f32;
             if drone.base_scans_cost == 0 {
-                state.score.dive_score += drone.pos.y / 10000. / 5 as f32;
+                state.score.dive_score += drone.pos.y / 10000. / self.params.dive_divisor;
+            }
+        }
+        self.score_combos(state, iter);
+    }
+    fn score_combos(&self, state: &mut GameState, iter: usize) {
+        for (i, goal) in self.combo_goals.iter().enumerate() {
+            if state.combos_done & 1 << i != 0 {
+                continue;
             }
+            let value = goal.bonus as f32 * self.params.combo_weight / iter as f32;
+            if state.surfaced & goal.drones == goal.drones {
+                state.score.combo_score += value;
+            } else if iter >= goal.deadline {
+                state.score.combo_score -= value;
+            } else {
+                continue;
+            }
+            state.combos_done |= 1 << i;
+        }
+    }
+    pub(crate) fn simulate_opponents(
+        &mut self,
+        state: &mut GameState,
+        actions: &Actions,
+        iter: usize,
+    ) {
+        for (i, action) in actions.iter().enumerate().take(state.opponent_count) {
+            let opponent = &mut state.opponents[i];
+            if opponent.emergency_turns > 0 {
+                Self::float_up(opponent);
+                continue;
+            }
+            opponent.pos = (opponent.pos + action.get_move())
+                .clamp(Vec2::new(0., 0.), Vec2::new(9999., 9999.));
+            if action.light && opponent.bat >= LIGHT_COST {
+                opponent.bat -= LIGHT_COST;
+                let opponent_pos = opponent.pos;
+                let aggression: f32 = self
+                    .tracker
+                    .monsters
+                    .iter()
+                    .filter(|m| (m.pos - opponent_pos).len() < POWERED_LIGHT_RADIUS)
+                    .flat_map(|m| {
+                        state.drones[..state.drone_count]
+                            .iter()
+                            .filter(|d| d.emergency_turns == 0 && !d.missing)
+                            .map(|d| {
+                                (1. - (m.pos - d.pos).len() / self.params.danger_radius).max(0.)
+                            })
+                    })
+                    .sum();
+                state.score.aggression_score -=
+                    aggression * self.params.aggression_weight / iter as f32;
+            } else {
+                opponent.bat = (opponent.bat + 1).min(MAX_BATTERY);
+            }
+            let opponent = &mut state.opponents[i];
+            if opponent.pos.y < 400. {
+                let stolen = state.drones.iter().fold(0, |mask, d| mask | d.race_scans)
+                    & opponent.race_scans;
+                opponent.race_scans = 0;
+                for drone in &mut state.drones {
+                    let lost = drone.race_scans & stolen;
+                    drone.race_scans &= !lost;
+                    drone.urgent_scans_cost -= scans_cost(&state.fish_costs, lost);
+                }
+                state.score.race_score -= scans_cost(&state.fish_costs, stolen) as f32
+                    * self.params.race_weight
+                    / iter as f32;
+            }
+        }
+    }
+    pub(crate) fn simulate_all_against(
+        &mut self,
+        state: &mut GameState,
+        gene: &mut Gene,
+        opponent_gene: &Gene,
+    ) {
+        for (iter, (action, opponent_actions)) in gene.iter_mut().zip(opponent_gene).enumerate() {
+            self.simulate(state, action, iter + 1);
+            self.simulate_opponents(state, opponent_actions, iter + 1);
+        }
+        self.total_simulations += 1;
+        if state.any_caught() {
+            self.dead_simulations += 1;
         }
     }
-    fn simulate_all(&mut self, state: &mut GameState, gene: &mut Gene) {
+    pub(crate) fn simulate_all(&mut self, state: &mut GameState, gene: &mut Gene) {
         for (iter, action) in gene.iter_mut().enumerate() {
             self.simulate(state, action, iter + 1);
         }
         self.total_simulations += 1;
-        if state.drones[0].dead || state.drones[1].dead {
+        if state.any_caught() {
+            self.dead_simulations += 1;
+        }
+    }
+    pub(crate) fn simulate_cached(
+        &mut self,
+        state_proto: &GameState,
+        gene: &mut Gene,
+        parent: &[StateHandle],
+        from: usize,
+        checkpoints: &mut Vec<StateHandle>,
+    ) -> GameState {
+        let interval = self.params.checkpoint_interval;
+        let reused = from.checked_div(interval).unwrap_or(0).min(parent.len());
+        for &handle in &parent[..reused] {
+            self.states.retain(handle);
+            checkpoints.push(handle);
+        }
+        let mut state = match checkpoints.last() {
+            Some(&handle) => self.states.get(handle).clone(),
+            None => state_proto.clone(),
+        };
+        let start = reused * interval;
+        for (iter, actions) in gene.iter_mut().enumerate().skip(start) {
+            self.simulate(&mut state, actions, iter + 1);
+            if interval > 0 && (iter + 1) % interval == 0 && iter + 1 < GENE_SIZE {
+                checkpoints.push(self.states.insert(&state));
+            }
+        }
+        self.total_simulations += 1;
+        self.reused_steps += start as i32;
+        if state.any_caught() {
             self.dead_simulations += 1;
         }
+        state
     }
 }
 }
 pub mod strategy {
+use std::{
+    fmt::Display,
+    time::{Duration, Instant},
+};
 use super::*;
+#[derive(Clone, Copy, Debug)]
+pub struct DroneCommand {
+    pub target: Vec2,
+    pub light: bool,
+    pub wait: bool,
+}
+impl Display for DroneCommand {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let (x, y) = (self.target.x as usize, self.target.y as usize);
+        let light = if self.light { 1 } else { 0 };
+        if self.wait {
+            write!(f, "WAIT {light}")
+        } else {
+            write!(f, "MOVE {x} {y} {light}")
+        }
+    }
+}
+#[derive(Clone, Copy, Default, Debug)]
+pub struct StageTimes {
+    pub tracker: Duration,
+    pub bounds: Duration,
+    pub maps: Duration,
+    pub search: Duration,
+}
 pub struct Strategy {
     pub bounds_detector: BoundsDetector,
     tracker: Tracker,
     pub exploration_map: ExplorationMap,
     pub score_map: ScoreMap,
-    pub pathfinding: Pathfinding,
+    pub allocation: TaskAllocation,
+    pub drone_score_maps: Vec<ScoreMap>,
+    pub battery: BatteryPlan,
+    pub planner: Box<dyn Planner>,
+    opening: Option<Opening>,
+    params: StrategyParams,
     pub meta_strategy: MetaStrategy,
+    drone_ids: Vec<i32>,
+    pub report: SearchReport,
+    pub game_report: GameReport,
+    pub times: StageTimes,
 }
 impl Strategy {
     pub fn new() -> Self {
+        Self::with_params(BAKED_PARAMS)
+    }
+    pub fn with_params(params: StrategyParams) -> Self {
         Strategy {
             bounds_detector: BoundsDetector::new(),
             tracker: Tracker::new(),
             exploration_map: ExplorationMap::new(),
             score_map: ScoreMap::new(),
-            pathfinding: Pathfinding::new(),
+            allocation: TaskAllocation::new(),
+            drone_score_maps: Vec::new(),
+            battery: BatteryPlan::default(),
+            planner: new_planner(params),
+            params,
             meta_strategy: MetaStrategy::new(),
+            opening: None,
+            drone_ids: Vec::new(),
+            report: SearchReport::default(),
+            game_report: GameReport::default(),
+            times: StageTimes::default(),
         }
     }
 }
 impl Strategy {
-    pub fn play(&mut self, world: &World) {
+    pub fn decide(&mut self, world: &World) -> Vec<DroneCommand> {
+        let start = Instant::now();
+        if self.drone_ids.is_empty() {
+            self.drone_ids = world.me.drones.keys().take(MAX_DRONES).copied().collect();
+            if world.me.drones.len() > MAX_DRONES {
+                eprintln!(
+                    "warning: {} drones, only the first {MAX_DRONES} are planned",
+                    world.me.drones.len()
+                );
+            }
+            if self.params.opening_book {
+                self.opening = Opening::find(world, &self.drone_ids);
+            }
+        }
         self.tracker.update(world);
+        self.times.tracker = start.elapsed();
         self.bounds_detector.update(world);
-        self.exploration_map.update(world);
+        self.times.bounds = start.elapsed() - self.times.tracker;
+        self.exploration_map.update(world, &self.params);
         self.meta_strategy.update(world);
         self.score_map
             .update(world, &self.bounds_detector, &self.meta_strategy);
-        let actions =
-            self.pathfinding
-                .search(world, &self.tracker, &self.exploration_map, &self.score_map);
-        for (i, drone) in world.me.drones.values().enumerate() {
-            let action = actions[i];
-            let pos = drone.pos + action.get_move();
-            let (x, y) = (pos.x as usize, pos.y as usize);
-            let light = if action.get_light() { 1 } else { 0 };
-            println!("MOVE {x} {y} {light}");
+        self.allocation.update(
+            world,
+            &self.bounds_detector,
+            &self.meta_strategy,
+            &self.drone_ids,
+        );
+        self.drone_score_maps
+            .resize_with(self.drone_ids.len(), ScoreMap::new);
+        for (drone_idx, map) in self.drone_score_maps.iter_mut().enumerate() {
+            let (allocation, bias) = (&self.allocation, self.params.allocation_bias);
+            map.update_weighted(world, &self.bounds_detector, &self.meta_strategy, |id| {
+                allocation.weight(id, drone_idx, bias)
+            });
+        }
+        self.battery
+            .update(world, &self.drone_score_maps, &self.drone_ids);
+        self.times.maps = start.elapsed() - self.times.tracker - self.times.bounds;
+        let ctx = PlanningContext {
+            world,
+            tracker: &self.tracker,
+            bounds_detector: &self.bounds_detector,
+            exploration_map: &self.exploration_map,
+            score_map: &self.score_map,
+            drone_score_maps: &self.drone_score_maps,
+            battery: &self.battery,
+            drone_ids: &self.drone_ids,
+        };
+        let budget =
+            Duration::from_millis(self.params.search_time_ms).saturating_sub(start.elapsed());
+        let search_start = Instant::now();
+        let book_plan = self
+            .opening
+            .as_ref()
+            .and_then(|opening| opening.plan(&ctx, &self.params, world.iter as usize));
+        if book_plan.is_none() {
+            self.opening = None;
+        }
+        let plan = if let Some(plan) = book_plan {
+            plan
+        } else if budget < Duration::from_millis(self.params.fallback_budget_ms) {
+            fallback_plan(&ctx, &self.params)
+        } else {
+            self.planner.plan(&ctx, budget)
+        };
+        self.times.search = search_start.elapsed();
+        let actions = plan.actions;
+        self.report = plan.report;
+        self.game_report.add(&self.report);
+        world
+            .me
+            .drones
+            .iter()
+            .map(
+                |(id, drone)| match self.drone_ids.iter().position(|i| i == id) {
+                    Some(i) => DroneCommand {
+                        target: drone.pos + actions[i].get_move(),
+                        light: actions[i].get_light(),
+                        wait: actions[i].is_wait(),
+                    },
+                    None => DroneCommand {
+                        target: drone.pos,
+                        light: false,
+                        wait: true,
+                    },
+                },
+            )
+            .collect()
+    }
+    pub fn context<'a>(&'a self, world: &'a World) -> PlanningContext<'a> {
+        PlanningContext {
+            world,
+            tracker: &self.tracker,
+            bounds_detector: &self.bounds_detector,
+            exploration_map: &self.exploration_map,
+            score_map: &self.score_map,
+            drone_score_maps: &self.drone_score_maps,
+            battery: &self.battery,
+            drone_ids: &self.drone_ids,
+        }
+    }
+    pub fn play(&mut self, world: &World) {
+        let commands = self.decide(world);
+        eprintln!("{}", self.report);
+        eprintln!("{}", self.game_report);
+        for command in commands {
+            println!("{command}");
         }
     }
 }
 }
+pub mod surfacing {
+use super::*;
+const SURFACE: f32 = 500.;
+#[derive(Clone, Copy, Debug)]
+pub struct ComboGoal {
+    pub drones: u8,
+    pub bonus: i32,
+    pub deadline: usize,
+}
+fn turns_to_surface(drone: &Drone) -> usize {
+    ((drone.pos.y - SURFACE) / DRONE_SPEED).ceil().max(0.) as usize
+}
+fn combos(world: &World) -> Vec<(Vec<i32>, i32)> {
+    let fish = world.creatures.values().filter(|c| c.typ != -1);
+    let by_type = (0..3).map(|typ| {
+        let ids = fish.clone().filter(|c| c.typ == typ).map(|c| c.id);
+        (ids.collect(), 4)
+    });
+    let by_color = (0..4).map(|color| {
+        let ids = fish.clone().filter(|c| c.color == color).map(|c| c.id);
+        (ids.collect(), 3)
+    });
+    by_type.chain(by_color).collect()
+}
+fn opponent_deadline(world: &World, ids: &[i32]) -> Option<usize> {
+    ids.iter()
+        .filter(|id| !world.opponent.scans.contains(id))
+        .map(|id| {
+            world
+                .opponent
+                .drones
+                .values()
+                .filter(|d| d.emergency == 0 && d.scans.contains(id))
+                .map(turns_to_surface)
+                .min()
+        })
+        .try_fold(0, |deadline, turns| Some(deadline.max(turns?)))
+}
+pub fn combo_goals(world: &World, drone_ids: &[i32]) -> Vec<ComboGoal> {
+    let drones = drone_ids
+        .iter()
+        .take(MAX_DRONES)
+        .map(|id| world.me.drones.get(id).filter(|d| d.emergency == 0))
+        .collect::<Vec<_>>();
+    combos(world)
+        .into_iter()
+        .filter(|(ids, _)| !ids.iter().all(|id| world.opponent.scans.contains(id)))
+        .filter_map(|(ids, bonus)| {
+            let mut mask = 0u8;
+            for id in ids.iter().filter(|id| !world.me.scans.contains(id)) {
+                let carrier = drones
+                    .iter()
+                    .enumerate()
+                    .filter_map(|(slot, d)| d.filter(|d| d.scans.contains(id)).map(|d| (slot, d)))
+                    .min_by_key(|(_, d)| turns_to_surface(d))?;
+                mask |= 1 << carrier.0;
+            }
+            (mask.count_ones() > 1).then(|| ComboGoal {
+                drones: mask,
+                bonus: 2 * bonus,
+                deadline: opponent_deadline(world, &ids).map_or(GENE_SIZE, |turns| turns.max(1)),
+            })
+        })
+        .collect()
+}
+}
 pub mod tracker {
 use std::collections::HashMap;
 use super::*;
+const DANGER_RADIUS: f32 = 1200.;
 #[derive(Clone)]
 pub struct Monster {
     id: i32,
@@ -794,6 +3906,11 @@ pub struct Monster {
     pub pos: Vec2,
     target: Option<Vec2>,
 }
+impl Monster {
+    pub fn pos_at(&self, turns: usize) -> Vec2 {
+        self.pos + self.vel * turns as f32
+    }
+}
 pub struct Tracker {
     pub monsters: Vec<Monster>,
     drone_bat: HashMap<i32, i32>,
@@ -891,24 +4008,17 @@ impl Tracker {
             self.drone_bat.insert(d.id, d.bat);
         }
     }
+    pub fn is_dangerous(&self, pos: Vec2) -> bool {
+        self.monsters
+            .iter()
+            .any(|m| (m.pos - pos).len() < DANGER_RADIUS)
+    }
     pub fn update(&mut self, world: &World) {
         self.update_monster_velocities(world);
         self.update_monster_positions();
         self.update_visible(world);
         self.update_monsters_targets(world);
         self.update_bat(world);
-        eprintln!("Monster tracking:");
-        for m in &self.monsters {
-            eprintln!(
-                "id:{}, pos: {}:{} vel: {}:{} target: {}",
-                m.id,
-                m.pos.x as i32,
-                m.pos.y as i32,
-                m.vel.x as i32,
-                m.vel.y as i32,
-                if m.target.is_some() { "yes" } else { "No" }
-            );
-        }
     }
 }
 }
@@ -1087,26 +4197,34 @@ impl World {
     }
 }
 }
+pub use allocation::*;
+pub use arena::*;
+pub use baked_book::*;
+pub use baked_params::*;
+pub use battery::*;
+pub use beam_search::*;
 pub use bounds_detector::*;
+pub use cli::*;
+pub use fallback::*;
+pub use grid::*;
+pub use input::*;
 pub use maps::*;
+pub use mcts::*;
 pub use meta_strategy::*;
+pub use opening_book::*;
+pub use params::*;
 pub use pathfinding::*;
+pub use planner::*;
+pub use random::*;
+pub use simulation::*;
 pub use strategy::*;
+pub use surfacing::*;
 pub use tracker::*;
 pub use vec2::*;
 pub use world::*;
 }
-use std::{collections::HashMap, io};
-use cgbot::{
-    strategy::Strategy,
-    vec2::Vec2,
-    world::{BlipDirection, Creature, Drone, World},
-};
-macro_rules! parse_input {
-    ($x:expr, $t:ident) => {
-        $x.trim().parse::<$t>().unwrap()
-    };
-}
+use std::io;
+use cgbot::{input, strategy::Strategy};
 #[cfg(debug_assertions)]
 fn check_debug() {
     eprintln!("Debugging enabled");
@@ -1117,162 +4235,13 @@ fn check_debug() {
 }
 fn main() {
     check_debug();
-    let mut input_line = String::new();
-    io::stdin().read_line(&mut input_line).unwrap();
-    let creature_count = parse_input!(input_line, usize);
-    let mut creatures = HashMap::with_capacity(creature_count);
-    for _ in 0..creature_count as usize {
-        let mut input_line = String::new();
-        io::stdin().read_line(&mut input_line).unwrap();
-        let inputs = input_line.split(" ").collect::<Vec<_>>();
-        let id = parse_input!(inputs[0], i32);
-        let color = parse_input!(inputs[1], i8);
-        let typ = parse_input!(inputs[2], i8);
-        creatures.insert(
-            id,
-            Creature {
-                id,
-                color,
-                typ,
-                ..Default::default()
-            },
-        );
-    }
-    let mut world = World {
-        creatures,
-        ..Default::default()
-    };
+    let mut stdin = io::stdin().lock();
+    let mut world = input::read_creatures(&mut stdin);
     let mut strategy = Strategy::new();
     let mut iter = 0;
     loop {
-        world.clear();
+        input::read_turn(&mut stdin, &mut world);
         world.iter = iter;
-        let mut input_line = String::new();
-        io::stdin().read_line(&mut input_line).unwrap();
-        world.me.score = parse_input!(input_line, i32);
-        input_line.clear();
-        io::stdin().read_line(&mut input_line).unwrap();
-        world.opponent.score = parse_input!(input_line, i32);
-        input_line.clear();
-        io::stdin().read_line(&mut input_line).unwrap();
-        let my_scan_count = parse_input!(input_line, usize);
-        world.me.scans.reserve(my_scan_count);
-        input_line.clear();
-        for _ in 0..my_scan_count as usize {
-            io::stdin().read_line(&mut input_line).unwrap();
-            let creature_id = parse_input!(input_line, i32);
-            world.me.scans.insert(creature_id);
-            input_line.clear();
-        }
-        io::stdin().read_line(&mut input_line).unwrap();
-        let foe_scan_count = parse_input!(input_line, usize);
-        world.opponent.scans.reserve(foe_scan_count);
-        input_line.clear();
-        for _ in 0..foe_scan_count as usize {
-            io::stdin().read_line(&mut input_line).unwrap();
-            let creature_id = parse_input!(input_line, i32);
-            world.opponent.scans.insert(creature_id);
-            input_line.clear();
-        }
-        io::stdin().read_line(&mut input_line).unwrap();
-        let my_drone_count = parse_input!(input_line, usize);
-        input_line.clear();
-        for _ in 0..my_drone_count as usize {
-            io::stdin().read_line(&mut input_line).unwrap();
-            let inputs = input_line.split(" ").collect::<Vec<_>>();
-            let id = parse_input!(inputs[0], i32);
-            let x = parse_input!(inputs[1], i32) as f32;
-            let y = parse_input!(inputs[2], i32) as f32;
-            let emergency = parse_input!(inputs[3], i32);
-            let bat = parse_input!(inputs[4], i32);
-            world.me.drones.insert(
-                id,
-                Drone {
-                    id,
-                    pos: Vec2 { x, y },
-                    bat,
-                    emergency,
-                    ..Default::default()
-                },
-            );
-            input_line.clear();
-        }
-        io::stdin().read_line(&mut input_line).unwrap();
-        let foe_drone_count = parse_input!(input_line, usize);
-        input_line.clear();
-        for _ in 0..foe_drone_count as usize {
-            io::stdin().read_line(&mut input_line).unwrap();
-            let inputs = input_line.split(" ").collect::<Vec<_>>();
-            let id = parse_input!(inputs[0], i32);
-            let x = parse_input!(inputs[1], i32) as f32;
-            let y = parse_input!(inputs[2], i32) as f32;
-            let emergency = parse_input!(inputs[3], i32);
-            let bat = parse_input!(inputs[4], i32);
-            world.opponent.drones.insert(
-                id,
-                Drone {
-                    id,
-                    pos: Vec2 { x, y },
-                    bat,
-                    emergency,
-                    ..Default::default()
-                },
-            );
-            input_line.clear();
-        }
-        io::stdin().read_line(&mut input_line).unwrap();
-        let drone_scan_count = parse_input!(input_line, i32);
-        input_line.clear();
-        for _ in 0..drone_scan_count as usize {
-            io::stdin().read_line(&mut input_line).unwrap();
-            let inputs = input_line.split(" ").collect::<Vec<_>>();
-            let drone_id = parse_input!(inputs[0], i32);
-            let creature_id = parse_input!(inputs[1], i32);
-            if let Some(drone) = world.me.drones.get_mut(&drone_id) {
-                drone.scans.insert(creature_id);
-            } else if let Some(drone) = world.opponent.drones.get_mut(&drone_id) {
-                drone.scans.insert(creature_id);
-            } else {
-                unreachable!()
-            }
-            input_line.clear();
-        }
-        io::stdin().read_line(&mut input_line).unwrap();
-        let visible_creature_count = parse_input!(input_line, i32);
-        input_line.clear();
-        for creature in world.creatures.values_mut() {
-            creature.clear();
-        }
-        for _ in 0..visible_creature_count as usize {
-            io::stdin().read_line(&mut input_line).unwrap();
-            let inputs = input_line.split(" ").collect::<Vec<_>>();
-            let id = parse_input!(inputs[0], i32);
-            let x = parse_input!(inputs[1], i32) as f32;
-            let y = parse_input!(inputs[2], i32) as f32;
-            let vx = parse_input!(inputs[3], i32) as f32;
-            let vy = parse_input!(inputs[4], i32) as f32;
-            if let Some(creature) = world.creatures.get_mut(&id) {
-                creature.pos = Some(Vec2 { x, y });
-                creature.speed = Some(Vec2 { x: vx, y: vy });
-            }
-            input_line.clear();
-        }
-        io::stdin().read_line(&mut input_line).unwrap();
-        let radar_blip_count = parse_input!(input_line, i32);
-        input_line.clear();
-        for _ in 0..radar_blip_count as usize {
-            io::stdin().read_line(&mut input_line).unwrap();
-            let inputs = input_line.split(" ").collect::<Vec<_>>();
-            let drone_id = parse_input!(inputs[0], i32);
-            let creature_id = parse_input!(inputs[1], i32);
-            let radar = inputs[2].trim().to_string();
-            if let Some(drone) = world.me.drones.get_mut(&drone_id) {
-                drone
-                    .blips
-                    .insert(creature_id, BlipDirection::from_str(&radar));
-            }
-            input_line.clear();
-        }
         strategy.play(&world);
         iter += 1;
     }
@@ -0,0 +1,189 @@
+//! SPSA tuner for the strategy parameters.
+//!
+//! Every candidate is evaluated by playing seeded arena games against a fixed
+//! reference bot, once from each side. Arguments are `key=value` pairs:
+//!
+//! ```text
+//! cargo run --release --bin tune -- iterations=100 games=4 out=params/tuned.params 2>/dev/null
+//! ```
+extern crate cgbot;
+
+use std::{fs, io::Write};
+
+use cgbot::*;
+
+use rand::{rngs::StdRng, Rng, SeedableRng};
+
+/// Tuned parameters with their search ranges.
+const TUNABLES: &[(&str, f32, f32)] = &[
+    ("visited_score", 0., 0.5),
+    ("light_threshold", 0., 5.),
+    ("scan_risk_weight", 0., 1.),
+    ("depth_risk", 0., 3.),
+    ("danger_radius", 500., 5000.),
+    ("dive_divisor", 1., 20.),
+    ("overlap_weight", 0., 2.),
+    ("allocation_bias", 0., 1.),
+    ("coverage_weight", 0., 1.),
+    ("dead_penalty", 100., 5000.),
+    ("lost_scans_weight", 0., 5.),
+    ("population_size", 10., 60.),
+    ("mutations_size", 5., 60.),
+    ("mutations_count", 1., 10.),
+    ("random_size", 0., 30.),
+    ("crossover_size", 5., 60.),
+    ("angle_sigma", 0.05, 2.),
+    ("gaussian_rate", 0., 1.),
+    ("replace_rate", 0., 1.),
+    ("reverse_rate", 0., 1.),
+    ("shift_rate", 0., 1.),
+    ("light_toggle_rate", 0., 1.),
+    ("light_repair_rate", 0., 1.),
+    ("uniform_crossover_rate", 0., 1.),
+    ("tournament_size", 1., 8.),
+    ("elite_size", 1., 20.),
+    ("thrust_sigma", 0.05, 1.),
+    ("wait_rate", 0., 0.5),
+];
+
+struct Config {
+    iterations: usize,
+    games: u64,
+    seed: u64,
+    search_time_ms: u64,
+    start: StrategyParams,
+    reference: StrategyParams,
+    out: String,
+    log: String,
+}
+
+impl Config {
+    fn from_args() -> Self {
+        let args = Args::from_env();
+
+        Config {
+            iterations: args.value("iterations", "100"),
+            games: args.value("games", "4"),
+            seed: args.value("seed", "1"),
+            search_time_ms: args.value("search_time_ms", "5"),
+            start: args.params("start", StrategyParams::default()),
+            reference: args.params("reference", StrategyParams::default()),
+            out: args.get("out", "params/tuned.params"),
+            log: args.get("log", "tune.csv"),
+        }
+    }
+}
+
+fn to_params(theta: &[f32], config: &Config) -> StrategyParams {
+    let mut params = config.start;
+    for (&(key, min, max), &t) in TUNABLES.iter().zip(theta) {
+        params.set_f32(key, min + (max - min) * t).unwrap();
+    }
+    params.search_time_ms = config.search_time_ms;
+    params
+}
+
+fn to_theta(params: &StrategyParams) -> Vec<f32> {
+    TUNABLES
+        .iter()
+        .map(|&(key, min, max)| ((params.get_f32(key).unwrap() - min) / (max - min)).clamp(0., 1.))
+        .collect()
+}
+
+/// Average score difference against the reference bot over `games` seeds,
+/// each played from both sides.
+fn evaluate(params: StrategyParams, config: &Config, first_seed: u64) -> f32 {
+    let mut reference_params = config.reference;
+    reference_params.search_time_ms = config.search_time_ms;
+
+    let mut total = 0;
+    for seed in first_seed..first_seed + config.games {
+        for side in 0..2 {
+            let mut candidate = Strategy::with_params(params);
+            let mut reference = Strategy::with_params(reference_params);
+
+            let mut arena = Arena::new(seed);
+            let scores = if side == 0 {
+                arena.play([&mut candidate, &mut reference])
+            } else {
+                arena.play([&mut reference, &mut candidate])
+            };
+
+            total += scores[side] - scores[1 - side];
+        }
+    }
+
+    total as f32 / (2 * config.games) as f32
+}
+
+fn main() {
+    let config = Config::from_args();
+    let mut rng = StdRng::seed_from_u64(config.seed);
+
+    let mut log = fs::File::create(&config.log).unwrap();
+    let header = TUNABLES.iter().map(|(key, _, _)| *key).collect::<Vec<_>>();
+    writeln!(log, "iteration,plus,minus,{}", header.join(",")).unwrap();
+
+    let mut theta = to_theta(&config.start);
+    let mut best = (f32::MIN, to_params(&theta, &config));
+
+    // Standard SPSA gain sequences.
+    let (a, c, big_a, alpha, gamma) = (0.002, 0.1, config.iterations as f32 * 0.1, 0.602, 0.101);
+
+    for k in 0..config.iterations {
+        let ak = a / (k as f32 + 1. + big_a).powf(alpha);
+        let ck = c / (k as f32 + 1.).powf(gamma);
+
+        let delta = TUNABLES
+            .iter()
+            .map(|_| if rng.gen_bool(0.5) { 1. } else { -1. })
+            .collect::<Vec<f32>>();
+
+        let shifted = |sign: f32| {
+            theta
+                .iter()
+                .zip(&delta)
+                .map(|(t, d)| (t + sign * ck * d).clamp(0., 1.))
+                .collect::<Vec<_>>()
+        };
+        let (theta_plus, theta_minus) = (shifted(1.), shifted(-1.));
+
+        // Both sides of the gradient estimate share seeds to reduce noise.
+        let first_seed = config.seed + k as u64 * config.games;
+        let params_plus = to_params(&theta_plus, &config);
+        let params_minus = to_params(&theta_minus, &config);
+        let plus = evaluate(params_plus, &config, first_seed);
+        let minus = evaluate(params_minus, &config, first_seed);
+
+        for (value, params) in [(plus, params_plus), (minus, params_minus)] {
+            if value > best.0 {
+                best = (value, params);
+
+                // The short tuning budget is not part of the result.
+                let tuned = StrategyParams {
+                    search_time_ms: config.start.search_time_ms,
+                    ..best.1
+                };
+                fs::write(&config.out, tuned.to_file_string()).unwrap();
+            }
+        }
+
+        for (t, d) in theta.iter_mut().zip(&delta) {
+            *t = (*t + ak * (plus - minus) / (2. * ck * d)).clamp(0., 1.);
+        }
+
+        let values = to_params(&theta, &config);
+        let values = TUNABLES
+            .iter()
+            .map(|(key, _, _)| values.get_f32(key).unwrap().to_string())
+            .collect::<Vec<_>>();
+        writeln!(log, "{k},{plus},{minus},{}", values.join(",")).unwrap();
+
+        println!(
+            "iteration {k}: plus {plus:.2} minus {minus:.2} best {:.2}",
+            best.0
+        );
+    }
+
+    println!("best parameters written to {}", config.out);
+}
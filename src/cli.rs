@@ -0,0 +1,52 @@
+use std::{collections::HashMap, fmt::Debug, path::Path, str::FromStr};
+
+use super::*;
+
+/// `key=value` arguments of the offline tools. Arguments without `=` are
+/// ignored.
+pub struct Args {
+    values: HashMap<String, String>,
+}
+
+impl Args {
+    pub fn from_env() -> Self {
+        Self::parse(std::env::args().skip(1))
+    }
+
+    pub fn parse(args: impl IntoIterator<Item = String>) -> Self {
+        let values = args
+            .into_iter()
+            .filter_map(|arg| {
+                arg.split_once('=')
+                    .map(|(k, v)| (k.to_string(), v.to_string()))
+            })
+            .collect();
+
+        Args { values }
+    }
+
+    pub fn opt(&self, key: &str) -> Option<String> {
+        self.values.get(key).cloned()
+    }
+
+    pub fn get(&self, key: &str, default: &str) -> String {
+        self.opt(key).unwrap_or(default.into())
+    }
+
+    /// Value of `key`, or `default` if missing. Panics on a bad value.
+    pub fn value<T: FromStr>(&self, key: &str, default: &str) -> T
+    where
+        T::Err: Debug,
+    {
+        self.get(key, default).parse().unwrap()
+    }
+
+    /// Parameters loaded from the file named by `key`, or `default` if
+    /// missing.
+    pub fn params(&self, key: &str, default: StrategyParams) -> StrategyParams {
+        match self.values.get(key) {
+            Some(path) => StrategyParams::load(Path::new(path)).unwrap(),
+            None => default,
+        }
+    }
+}
@@ -0,0 +1,84 @@
+use std::{f32::consts::PI, time::Instant};
+
+use super::*;
+
+fn cell_values(ctx: &PlanningContext) -> ScoreGrid {
+    let exploration = ctx.exploration_map.on_score_grid();
+
+    ScoreGrid::from_fn(|x, y| exploration[(x, y)] * ctx.score_map.get_score_by_idx(x, y))
+}
+
+/// Pull of every cell towards its centre, falling off with the distance.
+fn attraction(values: &ScoreGrid, pos: Vec2) -> Vec2 {
+    values
+        .iter()
+        .fold(Vec2::default(), |force, ((x, y), value)| {
+            let d = ScoreGrid::centre(x, y) - pos;
+            let dist = d.len().max(ScoreGrid::CELL_WIDTH);
+
+            force + d * (value / (dist * dist))
+        })
+}
+
+fn repulsion(tracker: &Tracker, params: &StrategyParams, pos: Vec2) -> Vec2 {
+    tracker.monsters.iter().fold(Vec2::default(), |force, m| {
+        let d = pos - m.pos;
+        let strength = (1. - d.len() / params.danger_radius).max(0.) * params.repulsion_weight;
+
+        force + d.norm() * strength
+    })
+}
+
+/// Gene of the potential field controller. Drones in turn are pulled to the
+/// value of `ScoreMap` × `ExplorationMap`, pushed away from the tracked
+/// monsters, and clear the cells they pass for themselves and the next
+/// drones.
+pub fn potential_field_gene(ctx: &PlanningContext, params: &StrategyParams) -> Gene {
+    let mut gene = Gene::default();
+    let mut values = cell_values(ctx);
+
+    for (drone_idx, id) in ctx.drone_ids.iter().enumerate().take(MAX_DRONES) {
+        let Some(drone) = ctx.world.me.drones.get(id) else {
+            continue;
+        };
+
+        let mut pos = drone.pos;
+        for actions in &mut gene {
+            values[ScoreGrid::cell(pos)] = 0.;
+
+            let dir = attraction(&values, pos).norm() + repulsion(ctx.tracker, params, pos);
+            let angle = if dir.len() > 0. {
+                dir.y.atan2(dir.x)
+            } else {
+                -PI / 2.
+            };
+
+            let action = Action {
+                angle,
+                light: false,
+                thrust: 1.,
+            };
+            pos = (pos + action.get_move()).clamp(Vec2::new(0., 0.), Vec2::new(9999., 9999.));
+            actions[drone_idx] = action;
+        }
+    }
+
+    gene
+}
+
+/// Plan of the potential field controller, for when there is no time left
+/// to search.
+pub fn fallback_plan(ctx: &PlanningContext, params: &StrategyParams) -> Plan {
+    let start = Instant::now();
+    let gene = potential_field_gene(ctx, params);
+
+    Plan {
+        actions: gene[0],
+        gene,
+        score: Score::default(),
+        report: SearchReport {
+            elapsed: start.elapsed(),
+            ..Default::default()
+        },
+    }
+}
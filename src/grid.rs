@@ -0,0 +1,228 @@
+use std::ops::{Index, IndexMut};
+
+use super::*;
+
+const MAP_SIZE: f32 = 10000.;
+
+/// Sample points along each side of a cell for its circle coverage.
+const COVERAGE_SAMPLES: usize = 4;
+
+/// Values over a `W`×`H` grid laid on the 10000×10000 map, indexed by
+/// `(column, row)`.
+#[derive(Clone, Copy)]
+pub struct Grid<T, const W: usize, const H: usize> {
+    cells: [[T; H]; W],
+}
+
+impl<T: Copy, const W: usize, const H: usize> Grid<T, W, H> {
+    pub const CELL_WIDTH: f32 = MAP_SIZE / W as f32;
+    pub const CELL_HEIGHT: f32 = MAP_SIZE / H as f32;
+
+    pub fn filled(value: T) -> Self {
+        Grid {
+            cells: [[value; H]; W],
+        }
+    }
+
+    pub fn from_fn(mut f: impl FnMut(usize, usize) -> T) -> Self {
+        Grid {
+            cells: std::array::from_fn(|x| std::array::from_fn(|y| f(x, y))),
+        }
+    }
+
+    pub fn fill(&mut self, value: T) {
+        self.cells = [[value; H]; W];
+    }
+
+    /// Cell containing `pos`, positions off the map go to the border cells.
+    pub fn cell(pos: Vec2) -> (usize, usize) {
+        let x = ((pos.x / Self::CELL_WIDTH) as usize).min(W - 1);
+        let y = ((pos.y / Self::CELL_HEIGHT) as usize).min(H - 1);
+        (x, y)
+    }
+
+    pub fn corner(x: usize, y: usize) -> Vec2 {
+        Vec2::new(x as f32 * Self::CELL_WIDTH, y as f32 * Self::CELL_HEIGHT)
+    }
+
+    pub fn centre(x: usize, y: usize) -> Vec2 {
+        Vec2::new(
+            (x as f32 + 0.5) * Self::CELL_WIDTH,
+            (y as f32 + 0.5) * Self::CELL_HEIGHT,
+        )
+    }
+
+    pub fn cells() -> impl Iterator<Item = (usize, usize)> {
+        (0..W).flat_map(|x| (0..H).map(move |y| (x, y)))
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = ((usize, usize), T)> + '_ {
+        Self::cells().map(|(x, y)| ((x, y), self.cells[x][y]))
+    }
+
+    pub fn iter_mut(&mut self) -> impl Iterator<Item = ((usize, usize), &mut T)> {
+        self.cells.iter_mut().enumerate().flat_map(|(x, column)| {
+            column
+                .iter_mut()
+                .enumerate()
+                .map(move |(y, value)| ((x, y), value))
+        })
+    }
+
+    /// Cells at most `reach` columns and rows away from `(x, y)`, inside the
+    /// grid.
+    pub fn neighbours(x: usize, y: usize, reach: usize) -> impl Iterator<Item = (usize, usize)> {
+        let xs = x.saturating_sub(reach)..=(x + reach).min(W - 1);
+        let ys = y.saturating_sub(reach)..=(y + reach).min(H - 1);
+
+        xs.flat_map(move |cx| ys.clone().map(move |cy| (cx, cy)))
+    }
+
+    /// Cells between the ones containing `top_left` and `bot_right`.
+    pub fn rect(top_left: Vec2, bot_right: Vec2) -> impl Iterator<Item = (usize, usize)> {
+        let (x0, y0) = Self::cell(top_left);
+        let (x1, y1) = Self::cell(bot_right);
+
+        (x0..=x1).flat_map(move |x| (y0..=y1).map(move |y| (x, y)))
+    }
+
+    /// Cells around the circle's bounding box.
+    fn circle_bounds(pos: Vec2, radius: f32) -> impl Iterator<Item = (usize, usize)> {
+        let reach = (radius / Self::CELL_WIDTH.min(Self::CELL_HEIGHT)).ceil() as usize;
+        let (x, y) = Self::cell(pos);
+
+        Self::neighbours(x, y, reach)
+    }
+
+    /// Cells whose centre is inside the circle.
+    pub fn circle(pos: Vec2, radius: f32) -> impl Iterator<Item = (usize, usize)> {
+        Self::circle_bounds(pos, radius)
+            .filter(move |&(x, y)| (Self::centre(x, y) - pos).len() <= radius)
+    }
+
+    /// Cells whose area intersects the circle, with the covered fraction of
+    /// their area estimated on a grid of sample points.
+    pub fn circle_coverage(pos: Vec2, radius: f32) -> impl Iterator<Item = ((usize, usize), f32)> {
+        let size = Vec2::new(Self::CELL_WIDTH, Self::CELL_HEIGHT);
+        let step = size * (1. / COVERAGE_SAMPLES as f32);
+
+        Self::circle_bounds(pos, radius).filter_map(move |(x, y)| {
+            let corner = Self::corner(x, y);
+            let nearest = pos.clamp(corner, corner + size);
+            if (nearest - pos).len() > radius {
+                return None;
+            }
+
+            let inside = (0..COVERAGE_SAMPLES)
+                .flat_map(|i| (0..COVERAGE_SAMPLES).map(move |j| (i, j)))
+                .filter(|&(i, j)| {
+                    let sample =
+                        corner + Vec2::new((i as f32 + 0.5) * step.x, (j as f32 + 0.5) * step.y);
+                    (sample - pos).len() <= radius
+                })
+                .count();
+
+            let coverage = inside as f32 / (COVERAGE_SAMPLES * COVERAGE_SAMPLES) as f32;
+            (coverage > 0.).then_some(((x, y), coverage))
+        })
+    }
+}
+
+impl<const W: usize, const H: usize> Grid<f32, W, H> {
+    /// Bilinear interpolation between the cell centres around `pos`. Exact
+    /// at the centres, flat past the outer ones.
+    pub fn sample(&self, pos: Vec2) -> f32 {
+        let fx = (pos.x / Self::CELL_WIDTH - 0.5).clamp(0., (W - 1) as f32);
+        let fy = (pos.y / Self::CELL_HEIGHT - 0.5).clamp(0., (H - 1) as f32);
+
+        let (x0, y0) = (fx as usize, fy as usize);
+        let (x1, y1) = ((x0 + 1).min(W - 1), (y0 + 1).min(H - 1));
+        let (tx, ty) = (fx - x0 as f32, fy - y0 as f32);
+
+        let top = self.cells[x0][y0] * (1. - tx) + self.cells[x1][y0] * tx;
+        let bottom = self.cells[x0][y1] * (1. - tx) + self.cells[x1][y1] * tx;
+
+        top * (1. - ty) + bottom * ty
+    }
+
+    /// The grid sampled at the cell centres of another resolution.
+    pub fn resample<const W2: usize, const H2: usize>(&self) -> Grid<f32, W2, H2> {
+        Grid::from_fn(|x, y| self.sample(Grid::<f32, W2, H2>::centre(x, y)))
+    }
+}
+
+impl<T, const W: usize, const H: usize> Index<(usize, usize)> for Grid<T, W, H> {
+    type Output = T;
+
+    fn index(&self, (x, y): (usize, usize)) -> &T {
+        &self.cells[x][y]
+    }
+}
+
+impl<T, const W: usize, const H: usize> IndexMut<(usize, usize)> for Grid<T, W, H> {
+    fn index_mut(&mut self, (x, y): (usize, usize)) -> &mut T {
+        &mut self.cells[x][y]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::f32::consts::PI;
+
+    use super::*;
+
+    type TestGrid = Grid<f32, 20, 10>;
+
+    #[test]
+    fn positions_off_the_map_go_to_the_border() {
+        assert_eq!(TestGrid::cell(Vec2::new(-100., -1.)), (0, 0));
+        assert_eq!(TestGrid::cell(Vec2::new(10000., 25000.)), (19, 9));
+        assert_eq!(TestGrid::cell(Vec2::new(9999., 0.)), (19, 0));
+
+        assert_eq!(TestGrid::neighbours(0, 9, 2).count(), 9);
+        assert_eq!(
+            TestGrid::rect(Vec2::new(-500., 9000.), Vec2::new(700., 12000.)).count(),
+            2
+        );
+    }
+
+    #[test]
+    fn circle_coverage_sums_to_the_circle_area() {
+        let cell_area = TestGrid::CELL_WIDTH * TestGrid::CELL_HEIGHT;
+
+        for radius in [800., 2000.] {
+            let area = TestGrid::circle_coverage(Vec2::new(4321., 5678.), radius)
+                .map(|(_, coverage)| coverage * cell_area)
+                .sum::<f32>();
+            let expected = PI * radius * radius;
+
+            assert!(
+                (area - expected).abs() < 0.05 * expected,
+                "radius {radius}: {area} vs {expected}"
+            );
+        }
+    }
+
+    #[test]
+    fn circle_coverage_is_cut_at_the_border() {
+        let cell_area = TestGrid::CELL_WIDTH * TestGrid::CELL_HEIGHT;
+        let area = TestGrid::circle_coverage(Vec2::new(0., 0.), 2000.)
+            .map(|(_, coverage)| coverage * cell_area)
+            .sum::<f32>();
+        let expected = PI * 2000. * 2000. / 4.;
+
+        assert!((area - expected).abs() < 0.05 * expected);
+    }
+
+    #[test]
+    fn sample_is_exact_at_cell_centres() {
+        let grid = TestGrid::from_fn(|x, y| (x * 10 + y) as f32);
+
+        for ((x, y), value) in grid.iter() {
+            assert_eq!(grid.sample(TestGrid::centre(x, y)), value);
+        }
+
+        let between = (TestGrid::centre(3, 4) + TestGrid::centre(4, 4)) * 0.5;
+        assert!((grid.sample(between) - 39.).abs() < 1e-3);
+    }
+}
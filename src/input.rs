@@ -0,0 +1,227 @@
+use std::{collections::HashMap, fmt::Write, fs, io::BufRead, path::Path};
+
+use super::*;
+
+macro_rules! parse_input {
+    ($x:expr, $t:ident) => {
+        $x.trim().parse::<$t>().unwrap()
+    };
+}
+
+fn read_line(input: &mut impl BufRead) -> String {
+    let mut line = String::new();
+    input.read_line(&mut line).unwrap();
+    line
+}
+
+fn read_drones(input: &mut impl BufRead) -> Vec<Drone> {
+    let count = parse_input!(read_line(input), usize);
+
+    (0..count)
+        .map(|_| {
+            let line = read_line(input);
+            let inputs = line.split(' ').collect::<Vec<_>>();
+            let x = parse_input!(inputs[1], i32) as f32;
+            let y = parse_input!(inputs[2], i32) as f32;
+
+            Drone {
+                id: parse_input!(inputs[0], i32),
+                pos: Vec2 { x, y },
+                emergency: parse_input!(inputs[3], i32),
+                bat: parse_input!(inputs[4], i32),
+                ..Default::default()
+            }
+        })
+        .collect()
+}
+
+/// Reads the creature list sent before the first turn.
+pub fn read_creatures(input: &mut impl BufRead) -> World {
+    let creature_count = parse_input!(read_line(input), usize);
+    let mut creatures = HashMap::with_capacity(creature_count);
+
+    for _ in 0..creature_count {
+        let line = read_line(input);
+        let inputs = line.split(' ').collect::<Vec<_>>();
+        let id = parse_input!(inputs[0], i32);
+        let color = parse_input!(inputs[1], i8);
+        let typ = parse_input!(inputs[2], i8);
+
+        creatures.insert(id, Creature::new(id, color, typ, None, None));
+    }
+
+    World {
+        creatures,
+        ..Default::default()
+    }
+}
+
+/// Reads one turn of input into `world`, replacing the previous turn.
+pub fn read_turn(input: &mut impl BufRead, world: &mut World) {
+    world.clear();
+
+    world.me.score = parse_input!(read_line(input), i32);
+    world.opponent.score = parse_input!(read_line(input), i32);
+
+    for player in [&mut world.me, &mut world.opponent] {
+        let scan_count = parse_input!(read_line(input), usize);
+        for _ in 0..scan_count {
+            player.scans.insert(parse_input!(read_line(input), i32));
+        }
+    }
+
+    for player in [&mut world.me, &mut world.opponent] {
+        for drone in read_drones(input) {
+            player.drones.insert(drone.id, drone);
+        }
+    }
+
+    let drone_scan_count = parse_input!(read_line(input), usize);
+    for _ in 0..drone_scan_count {
+        let line = read_line(input);
+        let inputs = line.split(' ').collect::<Vec<_>>();
+        let drone_id = parse_input!(inputs[0], i32);
+        let creature_id = parse_input!(inputs[1], i32);
+
+        if let Some(drone) = world.me.drones.get_mut(&drone_id) {
+            drone.scans.insert(creature_id);
+        } else if let Some(drone) = world.opponent.drones.get_mut(&drone_id) {
+            drone.scans.insert(creature_id);
+        } else {
+            unreachable!()
+        }
+    }
+
+    for creature in world.creatures.values_mut() {
+        creature.clear();
+    }
+
+    let visible_creature_count = parse_input!(read_line(input), usize);
+    for _ in 0..visible_creature_count {
+        let line = read_line(input);
+        let inputs = line.split(' ').collect::<Vec<_>>();
+        let id = parse_input!(inputs[0], i32);
+        let x = parse_input!(inputs[1], i32) as f32;
+        let y = parse_input!(inputs[2], i32) as f32;
+        let vx = parse_input!(inputs[3], i32) as f32;
+        let vy = parse_input!(inputs[4], i32) as f32;
+
+        if let Some(creature) = world.creatures.get_mut(&id) {
+            creature.pos = Some(Vec2 { x, y });
+            creature.speed = Some(Vec2 { x: vx, y: vy });
+        }
+    }
+
+    let radar_blip_count = parse_input!(read_line(input), usize);
+    for _ in 0..radar_blip_count {
+        let line = read_line(input);
+        let inputs = line.split(' ').collect::<Vec<_>>();
+        let drone_id = parse_input!(inputs[0], i32);
+        let creature_id = parse_input!(inputs[1], i32);
+
+        if let Some(drone) = world.me.drones.get_mut(&drone_id) {
+            drone
+                .blips
+                .insert(creature_id, BlipDirection::from_str(inputs[2].trim()));
+        }
+    }
+}
+
+/// Writes the world the way the referee sends it: the creature list, then
+/// one turn of input.
+pub fn write_input(world: &World) -> String {
+    let mut s = String::new();
+
+    let mut creatures = world.creatures.values().collect::<Vec<_>>();
+    creatures.sort_by_key(|c| c.id);
+
+    writeln!(s, "{}", creatures.len()).unwrap();
+    for c in &creatures {
+        writeln!(s, "{} {} {}", c.id, c.color, c.typ).unwrap();
+    }
+
+    writeln!(s, "{}\n{}", world.me.score, world.opponent.score).unwrap();
+
+    for player in [&world.me, &world.opponent] {
+        let mut scans = player.scans.iter().collect::<Vec<_>>();
+        scans.sort();
+
+        writeln!(s, "{}", scans.len()).unwrap();
+        for id in scans {
+            writeln!(s, "{id}").unwrap();
+        }
+    }
+
+    for player in [&world.me, &world.opponent] {
+        writeln!(s, "{}", player.drones.len()).unwrap();
+        for d in player.drones.values() {
+            let (x, y) = (d.pos.x as i32, d.pos.y as i32);
+            writeln!(s, "{} {x} {y} {} {}", d.id, d.emergency, d.bat).unwrap();
+        }
+    }
+
+    let mut drone_scans = Vec::new();
+    for drone in world
+        .me
+        .drones
+        .values()
+        .chain(world.opponent.drones.values())
+    {
+        let mut scans = drone.scans.iter().collect::<Vec<_>>();
+        scans.sort();
+        drone_scans.extend(scans.into_iter().map(|id| (drone.id, id)));
+    }
+
+    writeln!(s, "{}", drone_scans.len()).unwrap();
+    for (drone_id, creature_id) in drone_scans {
+        writeln!(s, "{drone_id} {creature_id}").unwrap();
+    }
+
+    let visible = creatures
+        .iter()
+        .filter_map(|c| Some((c.id, c.pos?, c.speed.unwrap_or_default())))
+        .collect::<Vec<_>>();
+
+    writeln!(s, "{}", visible.len()).unwrap();
+    for (id, pos, speed) in visible {
+        let (x, y, vx, vy) = (pos.x as i32, pos.y as i32, speed.x as i32, speed.y as i32);
+        writeln!(s, "{id} {x} {y} {vx} {vy}").unwrap();
+    }
+
+    let mut blips = Vec::new();
+    for drone in world.me.drones.values() {
+        let mut drone_blips = drone.blips.iter().collect::<Vec<_>>();
+        drone_blips.sort_by_key(|(id, _)| **id);
+        blips.extend(drone_blips.into_iter().map(|(id, dir)| (drone.id, id, dir)));
+    }
+
+    writeln!(s, "{}", blips.len()).unwrap();
+    for (drone_id, creature_id, dir) in blips {
+        writeln!(s, "{drone_id} {creature_id} {dir:?}").unwrap();
+    }
+
+    s
+}
+
+/// Saved world: the turn number followed by the referee input.
+pub fn save_snapshot(world: &World, path: &Path) -> Result<(), String> {
+    let text = format!("{}\n{}", world.iter, write_input(world));
+    fs::write(path, text).map_err(|e| format!("{}: {e}", path.display()))
+}
+
+pub fn load_snapshot(path: &Path) -> Result<World, String> {
+    let text = fs::read_to_string(path).map_err(|e| format!("{}: {e}", path.display()))?;
+    let (iter, input) = text
+        .split_once('\n')
+        .ok_or_else(|| format!("{}: empty snapshot", path.display()))?;
+
+    let mut input = input.as_bytes();
+    let mut world = read_creatures(&mut input);
+    read_turn(&mut input, &mut world);
+    world.iter = iter
+        .trim()
+        .parse()
+        .map_err(|e| format!("{}: {e}", path.display()))?;
+
+    Ok(world)
+}
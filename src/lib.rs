@@ -1,17 +1,51 @@
+pub mod allocation;
+pub mod arena;
+pub mod baked_book;
+pub mod baked_params;
+pub mod battery;
+pub mod beam_search;
 pub mod bounds_detector;
+pub mod cli;
+pub mod fallback;
+pub mod grid;
+pub mod input;
 pub mod maps;
+pub mod mcts;
 pub mod meta_strategy;
+pub mod opening_book;
+pub mod params;
 pub mod pathfinding;
+pub mod planner;
+pub mod random;
+pub mod simulation;
 pub mod strategy;
+pub mod surfacing;
 pub mod tracker;
 pub mod vec2;
 pub mod world;
 
+pub use allocation::*;
+pub use arena::*;
+pub use baked_book::*;
+pub use baked_params::*;
+pub use battery::*;
+pub use beam_search::*;
 pub use bounds_detector::*;
+pub use cli::*;
+pub use fallback::*;
+pub use grid::*;
+pub use input::*;
 pub use maps::*;
+pub use mcts::*;
 pub use meta_strategy::*;
+pub use opening_book::*;
+pub use params::*;
 pub use pathfinding::*;
+pub use planner::*;
+pub use random::*;
+pub use simulation::*;
 pub use strategy::*;
+pub use surfacing::*;
 pub use tracker::*;
 pub use vec2::*;
 pub use world::*;
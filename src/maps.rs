@@ -1,78 +1,107 @@
-use crate::{meta_strategy, Creature, MetaStrategy};
+use std::collections::HashMap;
 
-use super::{bounds_detector::BoundsDetector, vec2::Vec2, world::World};
+use crate::{
+    meta_strategy, Creature, MetaStrategy, StrategyParams, LIGHT_RADIUS, POWERED_LIGHT_RADIUS,
+};
 
-const E_CELLS: usize = 20;
-const E_CELL_SIZE: usize = 10000 / E_CELLS;
+use super::{bounds_detector::BoundsDetector, grid::Grid, vec2::Vec2, world::World};
 
+pub const E_CELLS: usize = 20;
 pub const S_CELLS: usize = 20;
-pub const S_CELL_SIZE: usize = 10000 / S_CELLS;
+
+pub type ExplorationGrid = Grid<f32, E_CELLS, E_CELLS>;
+pub type ScoreGrid = Grid<f32, S_CELLS, S_CELLS>;
 
 pub struct ExplorationMap {
-    pub map: [[f32; E_CELLS]; E_CELLS],
+    pub map: ExplorationGrid,
+    /// Battery of the drones on the previous turn, a drop means the light
+    /// was on.
+    drone_bat: HashMap<i32, i32>,
 }
 
 pub struct ScoreMap {
-    pub map: [[f32; S_CELLS]; S_CELLS],
+    pub map: ScoreGrid,
 }
 
-pub fn position_to_grid_cell(pos: Vec2, cell_size: usize) -> (usize, usize) {
-    (pos.x as usize / cell_size, pos.y as usize / cell_size)
+/// Fish still in the game that none of our drones has scanned.
+pub fn is_unscanned(creature: &Creature, world: &World) -> bool {
+    creature.typ != -1
+        && !ScoreMap::is_in_someone_scan(creature.id, world)
+        && ScoreMap::is_alive(creature.id, world)
+        && !world.me.scans.contains(&creature.id)
 }
 
 impl ExplorationMap {
     pub fn new() -> Self {
         ExplorationMap {
-            map: [[1.; E_CELLS]; E_CELLS],
+            map: ExplorationGrid::filled(1.),
+            drone_bat: HashMap::new(),
         }
     }
 
     const STEP: f32 = 0.01;
 
-    pub fn update(&mut self, world: &World) {
-        for x in 0..E_CELLS {
-            for y in 0..E_CELLS {
-                if self.map[x][y] < 1. {
-                    self.map[x][y] += Self::STEP;
-                }
+    /// Regrows every cell, then covers the scan circle of every drone that
+    /// is not in emergency. Opponent drones are counted with
+    /// `opponent_coverage`.
+    pub fn update(&mut self, world: &World, params: &StrategyParams) {
+        for (_, value) in self.map.iter_mut() {
+            if *value < 1. {
+                *value += Self::STEP;
             }
         }
 
-        for drone in world.me.drones.values() {
-            let (x, y) = position_to_grid_cell(drone.pos, E_CELL_SIZE);
+        let opponent_drones = world
+            .opponent
+            .drones
+            .values()
+            .filter(|_| params.opponent_coverage);
 
-            self.map[x][y] = 0.;
-        }
-    }
+        for drone in world.me.drones.values().chain(opponent_drones) {
+            let used_light = self
+                .drone_bat
+                .insert(drone.id, drone.bat)
+                .is_some_and(|old_bat| old_bat > drone.bat);
 
-    pub fn use_light(&mut self, pos: Vec2) {
-        let (x, y) = position_to_grid_cell(pos, E_CELL_SIZE);
-        let (x, y) = (x as i32, y as i32);
+            if drone.emergency == 1 {
+                continue;
+            }
 
-        for dx in -1..2 {
-            for dy in -1..2 {
-                if x + dx >= 0 && x + dx < E_CELLS as i32 && y + dy >= 0 && y + dy < E_CELLS as i32
-                {
-                    self.map[(x + dx) as usize][(y + dy) as usize] = 0.;
-                }
+            if used_light {
+                self.use_light(drone.pos, params.coverage_weight);
+            } else {
+                self.cover(drone.pos, LIGHT_RADIUS, params.coverage_weight);
             }
         }
     }
 
-    pub fn get_score_by_idx(&self, x: usize, y: usize) -> f32 {
-        self.map[x][y]
+    pub fn use_light(&mut self, pos: Vec2, weight: f32) {
+        self.cover(pos, POWERED_LIGHT_RADIUS, weight);
+    }
+
+    /// Scales every cell down by `weight` times the fraction of its area
+    /// inside the circle. Fish keep moving, so a scan says less than
+    /// everything about a cell.
+    pub fn cover(&mut self, pos: Vec2, radius: f32, weight: f32) {
+        for (cell, coverage) in ExplorationGrid::circle_coverage(pos, radius) {
+            self.map[cell] *= 1. - weight * coverage;
+        }
     }
 
     pub fn get_score(&self, pos: Vec2) -> f32 {
-        let (x, y) = position_to_grid_cell(pos, E_CELL_SIZE);
-        self.map[x][y]
+        self.map[ExplorationGrid::cell(pos)]
+    }
+
+    /// Exploration values at the centres of the score map cells.
+    pub fn on_score_grid(&self) -> ScoreGrid {
+        self.map.resample()
     }
 }
 
 impl ScoreMap {
     pub fn new() -> Self {
         ScoreMap {
-            map: [[0.; S_CELLS]; S_CELLS],
+            map: ScoreGrid::filled(0.),
         }
     }
 
@@ -99,44 +128,39 @@ impl ScoreMap {
         bounds_detector: &BoundsDetector,
         meta_strategy: &MetaStrategy,
     ) {
-        for x in 0..S_CELLS {
-            for y in 0..S_CELLS {
-                self.map[x][y] = 0.;
-            }
-        }
+        self.update_weighted(world, bounds_detector, meta_strategy, |_| 1.);
+    }
 
-        let creatures = world.creatures.values().filter(|c| {
-            c.typ != -1
-                && !Self::is_in_someone_scan(c.id, world)
-                && Self::is_alive(c.id, world)
-                && !world.me.scans.contains(&c.id)
-        });
+    /// Like `update`, with the cost of every fish scaled by `weight`.
+    pub fn update_weighted(
+        &mut self,
+        world: &World,
+        bounds_detector: &BoundsDetector,
+        meta_strategy: &MetaStrategy,
+        weight: impl Fn(i32) -> f32,
+    ) {
+        self.map.fill(0.);
+
+        let creatures = world.creatures.values().filter(|c| is_unscanned(c, world));
 
         for c in creatures {
             let bounds = bounds_detector.get_bounds(c.id);
 
-            let creature_cost = meta_strategy.get_fish_cost(c.id);
-
-            let (start_x, start_y) = position_to_grid_cell(bounds.top_left, S_CELL_SIZE);
-            let (end_x, end_y) = position_to_grid_cell(bounds.bot_right, S_CELL_SIZE);
-            let (end_x, end_y) = ((end_x + 1).min(S_CELLS), (end_y + 1).min(S_CELLS));
+            let creature_cost = meta_strategy.get_fish_cost(c.id) * weight(c.id);
 
-            let cells_count = (end_x - start_x) * (end_y - start_y);
+            let cells_count = ScoreGrid::rect(bounds.top_left, bounds.bot_right).count();
 
-            for x in start_x..end_x {
-                for y in start_y..end_y {
-                    self.map[x][y] += creature_cost / (cells_count as f32)
-                }
+            for cell in ScoreGrid::rect(bounds.top_left, bounds.bot_right) {
+                self.map[cell] += creature_cost / (cells_count as f32)
             }
         }
     }
 
     pub fn get_score(&self, pos: Vec2) -> f32 {
-        let (x, y) = position_to_grid_cell(pos, S_CELL_SIZE);
-        self.map[x][y]
+        self.map[ScoreGrid::cell(pos)]
     }
 
     pub fn get_score_by_idx(&self, x: usize, y: usize) -> f32 {
-        self.map[x][y]
+        self.map[(x, y)]
     }
 }
@@ -0,0 +1,238 @@
+use std::{
+    collections::HashMap,
+    time::{Duration, Instant},
+};
+
+use rand::Rng;
+
+use super::*;
+
+#[derive(Default, Clone, Copy)]
+struct Arm {
+    visits: u32,
+    total: f32,
+}
+
+impl Arm {
+    fn mean(&self) -> f32 {
+        self.total / self.visits as f32
+    }
+}
+
+/// Tree node with decoupled statistics: every drone picks its own arm and the
+/// joint pair selects the child.
+struct Node {
+    visits: u32,
+    arms: Vec<Vec<Arm>>,
+    children: HashMap<JointArms, usize>,
+}
+
+/// Arm picked by every drone slot.
+type JointArms = [usize; MAX_DRONES];
+
+impl Node {
+    fn new(drone_count: usize, arms_count: usize) -> Self {
+        Node {
+            visits: 0,
+            arms: vec![vec![Arm::default(); arms_count]; drone_count],
+            children: HashMap::new(),
+        }
+    }
+}
+
+/// Monte Carlo tree search with UCT over discretised headings, scored by
+/// random rollouts through `Simulation`.
+pub struct Mcts {
+    params: StrategyParams,
+    options: Vec<Action>,
+    nodes: Vec<Node>,
+    drone_count: usize,
+    best_by_root_arms: HashMap<JointArms, (f32, Gene)>,
+}
+
+impl Mcts {
+    pub fn new(params: StrategyParams) -> Self {
+        Mcts {
+            params,
+            options: Action::discrete(params.mcts_angles, params.encoding),
+            nodes: Vec::new(),
+            drone_count: 0,
+            best_by_root_arms: HashMap::new(),
+        }
+    }
+
+    fn select_arm(&self, node: &Node, drone: usize) -> usize {
+        let arms = &node.arms[drone];
+
+        let unvisited = arms
+            .iter()
+            .enumerate()
+            .filter(|(_, arm)| arm.visits == 0)
+            .map(|(i, _)| i)
+            .collect::<Vec<_>>();
+        if !unvisited.is_empty() {
+            return unvisited[planner_rng().gen_range(0..unvisited.len())];
+        }
+
+        // Raw scores have no fixed range, so exploration is scaled by the
+        // spread of the arm means.
+        let min = arms.iter().map(Arm::mean).fold(f32::MAX, f32::min);
+        let max = arms.iter().map(Arm::mean).fold(f32::MIN, f32::max);
+        let spread = if max > min { max - min } else { 1. };
+
+        let c = self.params.mcts_exploration * spread;
+        let ln_n = (node.visits.max(1) as f32).ln();
+        let ucb = |arm: &Arm| arm.mean() + c * (ln_n / arm.visits as f32).sqrt();
+
+        (0..arms.len())
+            .max_by(|&a, &b| ucb(&arms[a]).total_cmp(&ucb(&arms[b])))
+            .unwrap()
+    }
+
+    fn random_actions(&self) -> Actions {
+        let mut rng = planner_rng();
+        [(); MAX_DRONES].map(|_| self.options[rng.gen_range(0..self.options.len())])
+    }
+
+    fn new_node(&self) -> Node {
+        Node::new(self.drone_count, self.options.len())
+    }
+
+    fn iterate(&mut self, simulation: &mut Simulation, state_proto: &GameState) {
+        let mut state = state_proto.clone();
+        let mut gene = Gene::default();
+        let mut path = Vec::new();
+
+        let mut node = 0;
+        let mut depth = 0;
+
+        // Selection and expansion.
+        while depth < GENE_SIZE {
+            let mut arms = JointArms::default();
+            let mut actions = Actions::default();
+            for drone in 0..self.drone_count {
+                arms[drone] = self.select_arm(&self.nodes[node], drone);
+                actions[drone] = self.options[arms[drone]];
+            }
+
+            simulation.simulate(&mut state, &mut actions, depth + 1);
+            gene[depth] = actions;
+            depth += 1;
+
+            path.push((node, arms));
+
+            if let Some(&child) = self.nodes[node].children.get(&arms) {
+                node = child;
+            } else {
+                let child = self.nodes.len();
+                self.nodes.push(self.new_node());
+                self.nodes[node].children.insert(arms, child);
+                break;
+            }
+        }
+
+        // Rollout.
+        while depth < GENE_SIZE {
+            let mut actions = self.random_actions();
+            simulation.simulate(&mut state, &mut actions, depth + 1);
+            gene[depth] = actions;
+            depth += 1;
+        }
+        simulation.total_simulations += 1;
+
+        let reward = state.score.value();
+
+        let root_arms = path[0].1;
+        let best = self
+            .best_by_root_arms
+            .entry(root_arms)
+            .or_insert((f32::MIN, gene));
+        if reward > best.0 {
+            *best = (reward, gene);
+        }
+
+        // Backpropagation.
+        for (node, arms) in path {
+            let node = &mut self.nodes[node];
+            node.visits += 1;
+
+            for (drone_arms, &arm) in node.arms.iter_mut().zip(&arms) {
+                drone_arms[arm].visits += 1;
+                drone_arms[arm].total += reward;
+            }
+        }
+    }
+
+    fn most_visited(&self, drone: usize) -> usize {
+        let arms = &self.nodes[0].arms[drone];
+        (0..arms.len()).max_by_key(|&i| arms[i].visits).unwrap()
+    }
+}
+
+impl Planner for Mcts {
+    fn plan(&mut self, ctx: &PlanningContext, budget: Duration) -> Plan {
+        let start = Instant::now();
+
+        let params = self.params;
+        let mut simulation = Simulation::new(ctx, &params);
+        let state_proto = GameState::new(ctx);
+
+        self.drone_count = state_proto.drone_count;
+        self.nodes.clear();
+        self.nodes.push(self.new_node());
+        self.best_by_root_arms.clear();
+
+        loop {
+            self.iterate(&mut simulation, &state_proto);
+
+            let max = self.params.max_generations;
+            if Instant::now().duration_since(start) >= budget
+                || (max != 0 && self.nodes[0].visits as usize >= max)
+            {
+                break;
+            }
+        }
+
+        let mut arms = JointArms::default();
+        for (drone, arm) in arms.iter_mut().enumerate().take(self.drone_count) {
+            *arm = self.most_visited(drone);
+        }
+
+        // The most visited arms of both drones may never have been tried
+        // together, fall back to the best rollout in that case.
+        let (_, gene) = match self.best_by_root_arms.get(&arms) {
+            Some(&best) => best,
+            None => self
+                .best_by_root_arms
+                .values()
+                .copied()
+                .max_by(|a, b| a.0.total_cmp(&b.0))
+                .unwrap(),
+        };
+
+        let mut state = state_proto.clone();
+        let mut replay = gene;
+        simulation.simulate_all(&mut state, &mut replay);
+
+        Plan {
+            actions: replay[0],
+            gene: replay,
+            score: state.score,
+            report: SearchReport {
+                generations: self.nodes[0].visits as usize,
+                simulations: simulation.total_simulations,
+                dead_simulations: simulation.dead_simulations,
+                simulated_steps: simulation.simulated_steps,
+                score: state.score,
+                elapsed: start.elapsed(),
+                ..Default::default()
+            },
+        }
+    }
+
+    fn candidates(&self) -> Vec<Gene> {
+        let mut best = self.best_by_root_arms.values().collect::<Vec<_>>();
+        best.sort_by(|a, b| b.0.total_cmp(&a.0));
+        best.into_iter().map(|(_, gene)| *gene).collect()
+    }
+}
@@ -0,0 +1,109 @@
+use std::time::Instant;
+
+use super::*;
+
+/// Turns covered by every book line.
+pub const BOOK_TURNS: usize = 10;
+
+/// Turns ahead the book steps are checked against the tracked monsters.
+const SAFETY_TURNS: usize = 3;
+
+/// Distance from a book start that still counts as the same start.
+const START_TOLERANCE: f32 = 50.;
+
+/// Heading in radians and light of one book step.
+pub type BookStep = (f32, bool);
+
+/// Book step of a searched action. The book always moves at full thrust.
+pub fn book_step(action: &Action) -> BookStep {
+    (action.angle, action.light)
+}
+
+/// Dive of one drone, keyed by its position on the first turn.
+pub struct BookDrone {
+    pub start: (i32, i32),
+    pub steps: [BookStep; BOOK_TURNS],
+}
+
+/// Book lines of this game, by plan slot.
+pub struct Opening {
+    lines: Vec<&'static [BookStep; BOOK_TURNS]>,
+}
+
+impl Opening {
+    /// Book entry whose starts match all of our drones, if any.
+    pub fn find(world: &World, drone_ids: &[i32]) -> Option<Self> {
+        BAKED_BOOK.iter().find_map(|entry| {
+            let lines = drone_ids
+                .iter()
+                .map(|id| {
+                    let pos = world.me.drones.get(id)?.pos;
+                    entry.iter().find_map(|drone| {
+                        let start = Vec2::new(drone.start.0 as f32, drone.start.1 as f32);
+                        ((start - pos).len() <= START_TOLERANCE).then_some(&drone.steps)
+                    })
+                })
+                .collect::<Option<Vec<_>>>()?;
+
+            (lines.len() == entry.len()).then_some(Opening { lines })
+        })
+    }
+
+    /// Rest of the book from `turn`. `None` once the book is over, a drone is
+    /// in emergency or a step is dangerous, see `Tracker::is_dangerous`. The
+    /// steps are replayed through `Simulation`, which drops the lights the
+    /// battery or its plan can't afford.
+    pub fn plan(
+        &self,
+        ctx: &PlanningContext,
+        params: &StrategyParams,
+        turn: usize,
+    ) -> Option<Plan> {
+        if turn >= BOOK_TURNS {
+            return None;
+        }
+
+        let start = Instant::now();
+        let mut gene = Gene::default();
+
+        for (drone_idx, (id, steps)) in ctx.drone_ids.iter().zip(&self.lines).enumerate() {
+            let drone = ctx.world.me.drones.get(id)?;
+            if drone.emergency != 0 {
+                return None;
+            }
+
+            let mut pos = drone.pos;
+            for (step, (actions, &(angle, light))) in
+                gene.iter_mut().zip(&steps[turn..]).enumerate()
+            {
+                let action = Action {
+                    angle,
+                    light,
+                    thrust: 1.,
+                };
+                pos = (pos + action.get_move()).clamp(Vec2::new(0., 0.), Vec2::new(9999., 9999.));
+                if step < SAFETY_TURNS && ctx.tracker.is_dangerous(pos) {
+                    return None;
+                }
+                actions[drone_idx] = action;
+            }
+        }
+
+        let mut simulation = Simulation::new(ctx, params);
+        let mut state = GameState::new(ctx);
+        simulation.simulate_all(&mut state, &mut gene);
+
+        Some(Plan {
+            actions: gene[0],
+            gene,
+            score: state.score,
+            report: SearchReport {
+                simulations: simulation.total_simulations,
+                simulated_steps: simulation.simulated_steps,
+                score: state.score,
+                elapsed: start.elapsed(),
+                ..Default::default()
+            },
+        })
+    }
+}
@@ -0,0 +1,298 @@
+use std::{fmt::Debug, fs, path::Path};
+
+pub trait ParamValue: Sized + Debug {
+    fn parse_value(s: &str) -> Option<Self>;
+
+    fn to_f32(&self) -> f32;
+
+    fn from_f32(v: f32) -> Self;
+
+    fn to_source(&self) -> String {
+        format!("{:?}", self)
+    }
+
+    fn to_param(&self) -> String {
+        self.to_source()
+    }
+}
+
+impl ParamValue for f32 {
+    fn parse_value(s: &str) -> Option<Self> {
+        s.parse().ok()
+    }
+
+    fn to_f32(&self) -> f32 {
+        *self
+    }
+
+    fn from_f32(v: f32) -> Self {
+        v
+    }
+}
+
+impl ParamValue for usize {
+    fn parse_value(s: &str) -> Option<Self> {
+        s.parse().ok()
+    }
+
+    fn to_f32(&self) -> f32 {
+        *self as f32
+    }
+
+    fn from_f32(v: f32) -> Self {
+        v.round().max(0.) as usize
+    }
+}
+
+impl ParamValue for u64 {
+    fn parse_value(s: &str) -> Option<Self> {
+        s.parse().ok()
+    }
+
+    fn to_f32(&self) -> f32 {
+        *self as f32
+    }
+
+    fn from_f32(v: f32) -> Self {
+        v.round().max(0.) as u64
+    }
+}
+
+impl ParamValue for bool {
+    fn parse_value(s: &str) -> Option<Self> {
+        match s {
+            "true" | "1" => Some(true),
+            "false" | "0" => Some(false),
+            _ => None,
+        }
+    }
+
+    fn to_f32(&self) -> f32 {
+        *self as i32 as f32
+    }
+
+    fn from_f32(v: f32) -> Self {
+        v >= 0.5
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum PlannerKind {
+    Genetic,
+    Beam,
+    Mcts,
+}
+
+impl ParamValue for PlannerKind {
+    fn parse_value(s: &str) -> Option<Self> {
+        match s {
+            "genetic" => Some(PlannerKind::Genetic),
+            "beam" => Some(PlannerKind::Beam),
+            "mcts" => Some(PlannerKind::Mcts),
+            _ => None,
+        }
+    }
+
+    fn to_f32(&self) -> f32 {
+        *self as usize as f32
+    }
+
+    fn from_f32(v: f32) -> Self {
+        match v.round() as usize {
+            0 => PlannerKind::Genetic,
+            1 => PlannerKind::Beam,
+            _ => PlannerKind::Mcts,
+        }
+    }
+
+    fn to_source(&self) -> String {
+        format!("PlannerKind::{:?}", self)
+    }
+
+    fn to_param(&self) -> String {
+        match self {
+            PlannerKind::Genetic => "genetic".to_string(),
+            PlannerKind::Beam => "beam".to_string(),
+            PlannerKind::Mcts => "mcts".to_string(),
+        }
+    }
+}
+
+/// How a gene step moves a drone: a full-speed heading, or a heading with a
+/// thrust fraction where zero thrust is `WAIT`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum GeneEncoding {
+    Angle,
+    Thrust,
+}
+
+impl ParamValue for GeneEncoding {
+    fn parse_value(s: &str) -> Option<Self> {
+        match s {
+            "angle" => Some(GeneEncoding::Angle),
+            "thrust" => Some(GeneEncoding::Thrust),
+            _ => None,
+        }
+    }
+
+    fn to_f32(&self) -> f32 {
+        *self as usize as f32
+    }
+
+    fn from_f32(v: f32) -> Self {
+        match v.round() as usize {
+            0 => GeneEncoding::Angle,
+            _ => GeneEncoding::Thrust,
+        }
+    }
+
+    fn to_source(&self) -> String {
+        format!("GeneEncoding::{:?}", self)
+    }
+
+    fn to_param(&self) -> String {
+        match self {
+            GeneEncoding::Angle => "angle".to_string(),
+            GeneEncoding::Thrust => "thrust".to_string(),
+        }
+    }
+}
+
+macro_rules! strategy_params {
+    ($($name:ident: $t:ty = $default:expr,)*) => {
+        #[derive(Debug, Clone, Copy, PartialEq)]
+        pub struct StrategyParams {
+            $(pub $name: $t,)*
+        }
+
+        impl Default for StrategyParams {
+            fn default() -> Self {
+                StrategyParams {
+                    $($name: $default,)*
+                }
+            }
+        }
+
+        impl StrategyParams {
+            pub fn get_f32(&self, key: &str) -> Option<f32> {
+                match key {
+                    $(stringify!($name) => Some(self.$name.to_f32()),)*
+                    _ => None,
+                }
+            }
+
+            pub fn set_f32(&mut self, key: &str, value: f32) -> Result<(), String> {
+                match key {
+                    $(stringify!($name) => self.$name = <$t as ParamValue>::from_f32(value),)*
+                    _ => return Err(format!("unknown parameter: {key}")),
+                }
+                Ok(())
+            }
+
+            pub fn set(&mut self, key: &str, value: &str) -> Result<(), String> {
+                match key {
+                    $(stringify!($name) => {
+                        self.$name = <$t as ParamValue>::parse_value(value)
+                            .ok_or_else(|| format!("bad value for {key}: {value}"))?;
+                    })*
+                    _ => return Err(format!("unknown parameter: {key}")),
+                }
+                Ok(())
+            }
+
+            pub fn to_file_string(&self) -> String {
+                let mut s = String::new();
+                $(s += &format!("{} = {}\n", stringify!($name), self.$name.to_param());)*
+                s
+            }
+
+            pub fn to_baked_source(&self, origin: &str) -> String {
+                let mut s = format!("// Generated by build.rs from {origin}, do not edit.\n");
+                s += "use super::params::*;\n\n";
+                s += "pub const BAKED_PARAMS: StrategyParams = StrategyParams {\n";
+                $(s += &format!("    {}: {},\n", stringify!($name), self.$name.to_source());)*
+                s += "};\n";
+                s
+            }
+        }
+    };
+}
+
+strategy_params! {
+    planner: PlannerKind = PlannerKind::Genetic,
+    visited_score: f32 = 0.01,
+    light_threshold: f32 = 0.5,
+    scan_risk_weight: f32 = 0.2,
+    depth_risk: f32 = 1.,
+    danger_radius: f32 = 2500.,
+    dive_divisor: f32 = 5.,
+    dead_penalty: f32 = 1000.,
+    lost_scans_weight: f32 = 1.,
+    population_size: usize = 30,
+    mutations_size: usize = 30,
+    mutations_count: usize = 3,
+    random_size: usize = 10,
+    crossover_size: usize = 30,
+    search_time_ms: u64 = 40,
+    max_generations: usize = 0,
+    beam_width: usize = 4,
+    beam_angles: usize = 8,
+    mcts_angles: usize = 8,
+    mcts_exploration: f32 = 0.7,
+    coevolution: bool = false,
+    opponent_population_size: usize = 10,
+    opponent_responses: usize = 2,
+    race_weight: f32 = 1.,
+    combo_weight: f32 = 1.,
+    aggression_weight: f32 = 1.,
+    overlap_weight: f32 = 0.5,
+    allocation_bias: f32 = 0.5,
+    angle_sigma: f32 = 0.5,
+    gaussian_rate: f32 = 0.4,
+    replace_rate: f32 = 0.2,
+    reverse_rate: f32 = 0.1,
+    shift_rate: f32 = 0.1,
+    light_toggle_rate: f32 = 0.2,
+    uniform_crossover_rate: f32 = 0.5,
+    tournament_size: usize = 3,
+    elite_size: usize = 5,
+    checkpoint_interval: usize = 5,
+    encoding: GeneEncoding = GeneEncoding::Angle,
+    thrust_sigma: f32 = 0.2,
+    wait_rate: f32 = 0.1,
+    light_repair_rate: f32 = 0.3,
+    fallback_budget_ms: u64 = 5,
+    repulsion_weight: f32 = 2.,
+    opening_book: bool = true,
+    battery_planner: bool = true,
+    opponent_coverage: bool = false,
+    coverage_weight: f32 = 1.,
+}
+
+impl StrategyParams {
+    /// Parses `key = value` lines on top of the defaults. Empty lines and
+    /// lines starting with `#` are skipped.
+    pub fn parse(text: &str) -> Result<Self, String> {
+        let mut params = StrategyParams::default();
+
+        for line in text.lines() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+
+            let (key, value) = line
+                .split_once('=')
+                .ok_or_else(|| format!("expected key = value: {line}"))?;
+
+            params.set(key.trim(), value.trim())?;
+        }
+
+        Ok(params)
+    }
+
+    pub fn load(path: &Path) -> Result<Self, String> {
+        let text = fs::read_to_string(path).map_err(|e| format!("{}: {e}", path.display()))?;
+        Self::parse(&text)
+    }
+}
@@ -1,577 +1,847 @@
-use std::{f32::consts::PI, time::Instant};
+use std::{
+    f32::consts::PI,
+    time::{Duration, Instant},
+};
 
 use rand::Rng;
 
 use super::*;
 
-#[derive(Default, Clone, Copy)]
-pub struct Action {
-    angle: f32,
-    light: bool,
+/// Actions of a single drone over the whole horizon.
+pub type DronePlan = [Action; GENE_SIZE];
+
+/// Inherited plans are checked against the tracked monsters this many steps
+/// ahead, their extrapolated positions aren't reliable further.
+const REPAIR_STEPS: usize = 3;
+
+#[derive(Clone, Default)]
+pub struct Individual {
+    pub score: Score,
+    pub plan: DronePlan,
+    /// Cached `score.value()`, the sort key of the populations.
+    fitness: f32,
+    id: u64,
+    /// Joint gene the plan was scored with.
+    gene: Gene,
+    /// Game states along the joint gene, shared with the offspring.
+    checkpoints: Vec<StateHandle>,
 }
 
-impl Action {
-    pub fn get_light(&self) -> bool {
-        self.light
-    }
-
-    pub fn get_move(&self) -> Vec2 {
-        Vec2::new(1., 0.).rotate(self.angle) * 600.
-    }
+/// Genetic planner with one population per drone. A drone plan is scored
+/// jointly with the current best plans of the other drones.
+pub struct Pathfinding {
+    /// Individuals of all populations, released slots are reused.
+    pub individuals: Vec<Individual>,
+    free: Vec<usize>,
+    /// Indices into `individuals` by drone, best first.
+    pub populations: Vec<Vec<usize>>,
+    /// Drone id of every population.
+    drone_ids: Vec<i32>,
+    /// Empty buffer swapped with a population while it is rebuilt.
+    spare: Vec<usize>,
+    /// Best joint gene seen during the current search.
+    best: Option<(Score, Gene)>,
+    /// Ids of the partner plans each population was last scored with.
+    partners: Vec<Vec<u64>>,
+    next_id: u64,
+    /// Opponent plans ranked by how much they hurt our best plan. Only used
+    /// with `coevolution` enabled.
+    opponents: Vec<(f32, Gene)>,
+    params: StrategyParams,
 }
 
-#[derive(Default, Clone, Copy)]
-struct DroneState {
-    pos: Vec2,
-    bat: i32,
-    dead: bool,
-    base_scans_cost: i32,
-    urgent_scans_cost: i32,
+/// Standard normal sample by the Box-Muller transform.
+fn gaussian(rng: &mut impl Rng) -> f32 {
+    let u1 = rng.gen_range(f32::EPSILON..1.);
+    let u2 = rng.gen::<f32>();
+    (-2. * u1.ln()).sqrt() * (2. * PI * u2).cos()
 }
 
-#[derive(Clone, Copy, Default)]
-pub struct Score {
-    saving_scans_score: f32,
-    saving_urgent_scans_score: f32,
-    exploration_score: f32,
-    ascent_score: f32,
-    dive_score: f32,
-    dead_score: f32,
+/// Tournament over a population sorted from best to worst: the smallest
+/// sampled index wins.
+fn tournament(population_size: usize, tournament_size: usize, rng: &mut impl Rng) -> usize {
+    (0..tournament_size.max(1))
+        .map(|_| rng.gen_range(0..population_size))
+        .min()
+        .unwrap()
 }
 
-impl Score {
-    fn value(&self) -> f32 {
-        self.saving_scans_score
-            + self.saving_urgent_scans_score
-            + self.exploration_score
-            + self.dead_score
-            + self.ascent_score
-            + self.dive_score
+fn set_plan(gene: &mut Gene, drone_idx: usize, plan: &DronePlan) {
+    for (actions, action) in gene.iter_mut().zip(plan) {
+        actions[drone_idx] = *action;
     }
 }
 
-#[derive(Clone)]
-struct GameState {
-    drones: [DroneState; 2],
-    visited: [[bool; S_CELLS]; S_CELLS],
-    score: Score,
-    iter: i32,
-}
-
-fn estimate_drones_scans_profit(world: &World, drone: &Drone, state: &mut DroneState) {
-    let mut by_typ_count = [0; 3];
-    let mut by_color_count = [0; 4];
+fn split(gene: &Gene, drone_idx: usize) -> DronePlan {
+    let mut plan = DronePlan::default();
 
-    for id in &world.me.scans {
-        let creature = world.creatures.get(&id).unwrap();
-        by_typ_count[creature.typ as usize] += 1;
-        by_color_count[creature.color as usize] += 1;
+    for (i, actions) in gene.iter().enumerate() {
+        plan[i] = actions[drone_idx];
     }
 
-    for id in &drone.scans {
-        let creature = world.creatures.get(&id).unwrap();
-        let fish_cost = (creature.typ + 1) as i32;
-        state.base_scans_cost += fish_cost;
+    plan
+}
 
-        if !world.opponent.scans.contains(&id) {
-            state.urgent_scans_cost += fish_cost;
+impl Pathfinding {
+    pub fn new(params: StrategyParams) -> Self {
+        Pathfinding {
+            individuals: Vec::new(),
+            free: Vec::new(),
+            populations: Vec::new(),
+            drone_ids: Vec::new(),
+            spare: Vec::new(),
+            best: None,
+            partners: Vec::new(),
+            next_id: 0,
+            opponents: Vec::new(),
+            params,
         }
+    }
 
-        by_typ_count[creature.typ as usize] += 1;
-        by_color_count[creature.color as usize] += 1;
-
-        if by_typ_count[creature.typ as usize] == 4 {
-            state.base_scans_cost += 4;
-
-            let opponents_count = world
-                .opponent
-                .scans
-                .iter()
-                .filter(|id| world.creatures.get(&id).unwrap().typ == creature.typ)
-                .count();
-            if opponents_count < 4 {
-                state.urgent_scans_cost += 4;
-            }
+    /// Scores the gene against the most dangerous opponent plans and keeps
+    /// the worst outcome. Without co-evolution opponents stay still and the
+    /// simulation resumes from the parent checkpoints before step `from`.
+    fn evaluate(
+        &self,
+        simulation: &mut Simulation,
+        state_proto: &GameState,
+        gene: &mut Gene,
+        parent: &[StateHandle],
+        from: usize,
+        checkpoints: &mut Vec<StateHandle>,
+    ) -> Score {
+        if !self.params.coevolution || self.opponents.is_empty() {
+            return simulation
+                .simulate_cached(state_proto, gene, parent, from, checkpoints)
+                .score;
         }
 
-        if by_color_count[creature.color as usize] == 3 {
-            state.base_scans_cost += 3;
+        let mut worst: Option<(Score, Gene)> = None;
 
-            let opponents_count = world
-                .opponent
-                .scans
-                .iter()
-                .filter(|id| world.creatures.get(&id).unwrap().color == creature.color)
-                .count();
-            if opponents_count < 3 {
-                state.urgent_scans_cost += 3;
+        for (_, opponent_gene) in self
+            .opponents
+            .iter()
+            .take(self.params.opponent_responses.max(1))
+        {
+            let mut candidate = *gene;
+            let mut state = state_proto.clone();
+            simulation.simulate_all_against(&mut state, &mut candidate, opponent_gene);
+
+            let is_worse = match &worst {
+                Some((score, _)) => state.score.value() < score.value(),
+                None => true,
+            };
+            if is_worse {
+                worst = Some((state.score, candidate));
             }
         }
+
+        let (score, adjusted) = worst.unwrap();
+        *gene = adjusted;
+        score
     }
-}
 
-impl GameState {
-    fn new(world: &World) -> Self {
-        let mut drones = [DroneState::default(); 2];
-        for (i, drone) in world.me.drones.values().enumerate() {
-            drones[i].pos = drone.pos;
-            drones[i].bat = drone.bat;
-            drones[i].dead = drone.emergency == 1;
-            estimate_drones_scans_profit(world, drone, &mut drones[i]);
-        }
-        let visited = [[false; S_CELLS]; 20];
+    /// Joint gene of the plan with the best plans of the other drones.
+    fn with_partners(&self, drone_idx: usize, plan: &DronePlan) -> Gene {
+        let mut gene = self.straight_top_gene();
 
-        GameState {
-            drones,
-            visited,
-            score: Score::default(),
-            iter: world.iter,
+        for (i, population) in self.populations.iter().enumerate() {
+            if let Some(&partner) = population.first().filter(|_| i != drone_idx) {
+                set_plan(&mut gene, i, &self.individuals[partner].plan);
+            }
         }
-    }
+        set_plan(&mut gene, drone_idx, plan);
 
-    fn visit_score(&self, x: usize, y: usize) -> f32 {
-        if !self.visited[x][y] {
-            1.
-        } else {
-            1. / 100.
-        }
+        gene
     }
 
-    fn visit_cell(&mut self, x: usize, y: usize) {
-        self.visited[x][y] = true;
+    /// Ids of the best plans of the other drones.
+    fn partner_ids(&self, drone_idx: usize) -> Vec<u64> {
+        self.populations
+            .iter()
+            .enumerate()
+            .map(|(i, population)| match population.first() {
+                Some(&partner) if i != drone_idx => self.individuals[partner].id,
+                _ => 0,
+            })
+            .collect()
     }
-}
-
-const GENE_SIZE: usize = 25;
-const POPULATION_SIZE: usize = 30;
-const MUTATIONS_SIZE: usize = 30;
-const MUTATIONS_COUNT: usize = 3;
-const RANDOM_SIZE: usize = 10;
-const CROSSOVER_SIZE: usize = 30;
-
-type Gene = [[Action; 2]; GENE_SIZE];
-struct Simulation<'a> {
-    tracker: &'a Tracker,
-    exploration_map: &'a ExplorationMap,
-    score_map: &'a ScoreMap,
-    dead_simulations: i32,
-    total_simulations: i32,
-}
 
-pub struct Pathfinding {
-    pub population: Vec<(Score, Gene)>,
-}
+    fn alloc(&mut self) -> usize {
+        self.free.pop().unwrap_or_else(|| {
+            self.individuals.push(Individual::default());
+            self.individuals.len() - 1
+        })
+    }
 
-impl Pathfinding {
-    pub fn new() -> Self {
-        Pathfinding {
-            population: Vec::new(),
+    fn release(&mut self, simulation: &mut Simulation, idx: usize) {
+        let individual = &mut self.individuals[idx];
+        for handle in individual.checkpoints.drain(..) {
+            simulation.states.release(handle);
         }
+        self.free.push(idx);
     }
 
-    fn random_actions(&self) -> [Action; 2] {
-        let mut rng = rand::thread_rng();
-        let mut actions: [Action; 2] = Default::default();
+    /// Takes the population out, leaving the spare buffer in its place.
+    fn take_population(&mut self, drone_idx: usize) -> Vec<usize> {
+        let spare = std::mem::take(&mut self.spare);
+        std::mem::replace(&mut self.populations[drone_idx], spare)
+    }
 
-        for action in &mut actions {
-            action.light = rng.gen_bool(0.5);
-            action.angle = rng.gen_range(-PI..PI);
+    /// Releases the individuals of a taken population and keeps its buffer.
+    fn release_population(&mut self, simulation: &mut Simulation, mut population: Vec<usize>) {
+        for idx in population.drain(..) {
+            self.release(simulation, idx);
         }
+        self.spare = population;
+    }
 
-        actions
+    /// Scores the plan together with the best plans of the other drones into
+    /// a new slot. The simulation is shared with `parent` up to the first
+    /// changed step.
+    fn score_plan(
+        &mut self,
+        simulation: &mut Simulation,
+        state_proto: &GameState,
+        drone_idx: usize,
+        plan: &DronePlan,
+        parent: Option<usize>,
+    ) -> usize {
+        let mut gene = self.with_partners(drone_idx, plan);
+
+        let slot = self.alloc();
+        let mut checkpoints = std::mem::take(&mut self.individuals[slot].checkpoints);
+
+        let (cached, from) = match parent {
+            Some(parent) => {
+                let parent = &self.individuals[parent];
+                let from = parent
+                    .gene
+                    .iter()
+                    .zip(&gene)
+                    .position(|(a, b)| a != b)
+                    .unwrap_or(GENE_SIZE);
+                (&parent.checkpoints[..], from)
+            }
+            None => (&[][..], 0),
+        };
+
+        // Some offspring get their light bits repaired by the heuristic.
+        simulation.repair_light = planner_rng().gen::<f32>() < self.params.light_repair_rate;
+
+        let score = self.evaluate(
+            simulation,
+            state_proto,
+            &mut gene,
+            cached,
+            from,
+            &mut checkpoints,
+        );
+        simulation.repair_light = false;
+
+        self.individuals[slot] = Individual {
+            score,
+            plan: split(&gene, drone_idx),
+            fitness: score.value(),
+            id: 0,
+            gene,
+            checkpoints,
+        };
+        slot
     }
 
-    fn random_gene(&self) -> Gene {
-        let mut rng = rand::thread_rng();
-        let mut gene = Gene::default();
+    /// Adds the individual to the population of the drone, with a new id
+    /// unless it kept the one of a rescored plan.
+    fn add_individual(&mut self, drone_idx: usize, idx: usize) {
+        let individual = &mut self.individuals[idx];
+        if individual.id == 0 {
+            self.next_id += 1;
+            individual.id = self.next_id;
+        }
 
-        for actions in &mut gene {
-            for action in actions {
-                action.angle = rng.gen_range(-PI..PI);
-                action.light = false;
-            }
+        let is_best = match &self.best {
+            Some((best, _)) => individual.fitness > best.value(),
+            None => true,
+        };
+        if is_best {
+            self.best = Some((individual.score, individual.gene));
         }
 
-        gene
+        self.populations[drone_idx].push(idx);
     }
 
-    fn straight_top_gene(&self) -> Gene {
+    fn add_plan(
+        &mut self,
+        simulation: &mut Simulation,
+        state_proto: &GameState,
+        drone_idx: usize,
+        plan: &DronePlan,
+        parent: Option<usize>,
+    ) {
+        let idx = self.score_plan(simulation, state_proto, drone_idx, plan, parent);
+        self.add_individual(drone_idx, idx);
+    }
+
+    fn random_opponent_gene(&self) -> Gene {
         let mut gene = Gene::default();
 
         for actions in &mut gene {
-            for action in actions {
-                action.angle = -PI / 2.;
-                action.light = false;
-            }
+            *actions = self.random_actions();
         }
 
         gene
     }
 
-    fn add_straight_top(&mut self, simulation: &mut Simulation, state_proto: &GameState) {
-        let mut gene = self.straight_top_gene();
+    /// Opponent fitness is the damage done to our current best plan.
+    fn opponent_fitness(
+        &self,
+        simulation: &mut Simulation,
+        state_proto: &GameState,
+        opponent_gene: &Gene,
+    ) -> f32 {
+        let mut gene = match self.best {
+            Some((_, gene)) => gene,
+            None => self.straight_top_gene(),
+        };
         let mut state = state_proto.clone();
-        simulation.simulate_all(&mut state, &mut gene);
-        self.population.push((state.score, gene));
+        simulation.simulate_all_against(&mut state, &mut gene, opponent_gene);
+        -state.score.value()
     }
 
-    fn mutation(&mut self, simulation: &mut Simulation, state_proto: &GameState) {
-        let mut rng = rand::thread_rng();
-
-        let population_size = self.population.len();
+    fn evolve_opponents(&mut self, simulation: &mut Simulation, state_proto: &GameState) {
+        let mut rng = planner_rng();
 
-        for _ in 0..MUTATIONS_SIZE {
-            let idx = rng.gen_range(0..population_size);
+        let mut candidates = self
+            .opponents
+            .iter()
+            .map(|(_, gene)| *gene)
+            .collect::<Vec<_>>();
 
-            let (_, mut new_gene) = self.population[idx].clone();
+        for _ in 0..self.params.opponent_population_size {
+            let mut gene = if self.opponents.is_empty() {
+                self.random_opponent_gene()
+            } else {
+                self.opponents[rng.gen_range(0..self.opponents.len())].1
+            };
 
-            for _ in 0..MUTATIONS_COUNT {
-                let mut_idx = rng.gen_range(0..new_gene.len());
-                new_gene[mut_idx] = self.random_actions();
+            for _ in 0..self.params.mutations_count {
+                let mut_idx = rng.gen_range(0..gene.len());
+                gene[mut_idx] = self.random_actions();
             }
 
-            let mut state = state_proto.clone();
-            simulation.simulate_all(&mut state, &mut new_gene);
-            self.population.push((state.score, new_gene));
+            candidates.push(gene);
         }
+        candidates.push(self.straight_top_gene());
+
+        let mut opponents = candidates
+            .into_iter()
+            .map(|gene| (self.opponent_fitness(simulation, state_proto, &gene), gene))
+            .collect::<Vec<_>>();
+
+        opponents.sort_by(|a, b| b.0.total_cmp(&a.0));
+        opponents.truncate(self.params.opponent_population_size);
+
+        self.opponents = opponents;
     }
 
-    fn crossover(&mut self, simulation: &mut Simulation, state_proto: &GameState) {
-        let mut rng = rand::thread_rng();
+    fn shift_opponents(&mut self) {
+        for i in 0..self.opponents.len() {
+            let last = self.random_actions();
+            let gene = &mut self.opponents[i].1;
+            gene.rotate_left(1);
+            gene[GENE_SIZE - 1] = last;
+        }
+    }
 
-        let population_size = self.population.len();
+    /// Full speed with the angle encoding, otherwise a random fraction or
+    /// `WAIT` with `wait_rate` probability.
+    fn random_thrust(&self, rng: &mut impl Rng) -> f32 {
+        match self.params.encoding {
+            GeneEncoding::Angle => 1.,
+            GeneEncoding::Thrust if rng.gen::<f32>() < self.params.wait_rate => 0.,
+            GeneEncoding::Thrust => 1. - rng.gen::<f32>(),
+        }
+    }
 
-        for _ in 0..CROSSOVER_SIZE {
-            let idx1 = rng.gen_range(0..population_size);
-            let mut idx2 = rng.gen_range(0..population_size);
+    fn random_action(&self) -> Action {
+        let mut rng = planner_rng();
 
-            while idx2 == idx1 {
-                idx2 = rng.gen_range(0..population_size);
-            }
+        Action {
+            angle: rng.gen_range(-PI..PI),
+            light: rng.gen_bool(0.5),
+            thrust: self.random_thrust(&mut rng),
+        }
+    }
 
-            let (_, gene1) = &self.population[idx1];
-            let (_, gene2) = &self.population[idx2];
+    fn random_actions(&self) -> Actions {
+        [(); MAX_DRONES].map(|_| self.random_action())
+    }
 
-            let mut new_gene1 = gene1.clone();
-            let mut new_gene2 = gene2.clone();
+    fn random_plan(&self) -> DronePlan {
+        let mut rng = planner_rng();
+        let mut plan = DronePlan::default();
 
-            for i in 0..gene1.len() {
-                new_gene1[i][1] = gene2[i][1];
-                new_gene2[i][1] = gene1[i][1];
-            }
+        for action in &mut plan {
+            action.angle = rng.gen_range(-PI..PI);
+            action.light = false;
+            action.thrust = self.random_thrust(&mut rng);
+        }
 
-            let mut state = state_proto.clone();
-            simulation.simulate_all(&mut state, &mut new_gene1);
-            self.population.push((state.score, new_gene1));
+        plan
+    }
 
-            let mut state = state_proto.clone();
-            simulation.simulate_all(&mut state, &mut new_gene2);
-            self.population.push((state.score, new_gene2));
+    fn straight_top_plan(&self) -> DronePlan {
+        let mut plan = DronePlan::default();
+
+        for action in &mut plan {
+            action.angle = -PI / 2.;
+            action.light = false;
         }
+
+        plan
     }
 
-    fn add_randoms(&mut self, simulation: &mut Simulation, state_proto: &GameState) {
-        for _ in 0..RANDOM_SIZE {
-            let mut gene = self.random_gene();
-            let mut state = state_proto.clone();
+    fn straight_top_gene(&self) -> Gene {
+        let plan = self.straight_top_plan();
+        let mut gene = Gene::default();
 
-            simulation.simulate_all(&mut state, &mut gene);
-            self.population.push((state.score, gene));
+        for drone_idx in 0..MAX_DRONES {
+            set_plan(&mut gene, drone_idx, &plan);
         }
+
+        gene
     }
 
-    fn selection(&mut self) {
-        self.population
-            .sort_by_key(|(score, _)| -(score.value() * 100.) as i32);
+    fn add_straight_top(
+        &mut self,
+        simulation: &mut Simulation,
+        state_proto: &GameState,
+        drone_idx: usize,
+    ) {
+        let plan = self.straight_top_plan();
+        self.add_plan(simulation, state_proto, drone_idx, &plan, None);
+    }
 
-        self.population.truncate(POPULATION_SIZE);
+    /// Applies one mutation operator chosen by the configured rates.
+    fn mutate(&self, plan: &mut DronePlan, rng: &mut impl Rng) {
+        let rates = [
+            self.params.gaussian_rate,
+            self.params.replace_rate,
+            self.params.reverse_rate,
+            self.params.shift_rate,
+            self.params.light_toggle_rate,
+        ];
+        let total = rates.iter().map(|rate| rate.max(0.)).sum::<f32>();
+
+        let mut pick = rng.gen_range(0. ..total.max(f32::EPSILON));
+        let operator = rates
+            .iter()
+            .position(|rate| {
+                pick -= rate.max(0.);
+                pick < 0.
+            })
+            .unwrap_or(1);
+
+        let idx = rng.gen_range(0..GENE_SIZE);
+        let (from, to) = {
+            let other = rng.gen_range(0..GENE_SIZE);
+            (idx.min(other), idx.max(other) + 1)
+        };
+
+        match operator {
+            0 => {
+                let angle = plan[idx].angle + gaussian(rng) * self.params.angle_sigma;
+                plan[idx].angle = (angle + PI).rem_euclid(2. * PI) - PI;
+
+                if self.params.encoding == GeneEncoding::Thrust {
+                    let thrust = plan[idx].thrust + gaussian(rng) * self.params.thrust_sigma;
+                    plan[idx].thrust = thrust.clamp(0., 1.);
+                }
+            }
+            2 => plan[from..to].reverse(),
+            3 => {
+                if rng.gen_bool(0.5) {
+                    plan[from..to].rotate_left(1);
+                } else {
+                    plan[from..to].rotate_right(1);
+                }
+            }
+            4 => plan[idx].light = !plan[idx].light,
+            _ => plan[idx] = self.random_action(),
+        }
     }
 
-    fn modify_prev_generation(&mut self, simulation: &mut Simulation, state_proto: &GameState) {
-        let mut old_population = self.population.clone();
-        self.population.clear();
+    fn mutation(&mut self, simulation: &mut Simulation, state_proto: &GameState, drone_idx: usize) {
+        let mut rng = planner_rng();
 
-        for (_, action) in &mut old_population {
-            let mut gene = Gene::default();
+        let population_size = self.populations[drone_idx].len();
+        if population_size == 0 {
+            return;
+        }
 
-            for i in 1..action.len() {
-                gene[i - 1] = action[i];
-            }
+        for _ in 0..self.params.mutations_size {
+            let rank = tournament(population_size, self.params.tournament_size, &mut rng);
+            let idx = self.populations[drone_idx][rank];
 
-            gene[gene.len() - 1] = self.random_actions();
+            let mut new_plan = self.individuals[idx].plan;
 
-            let mut state = state_proto.clone();
-            simulation.simulate_all(&mut state, &mut gene);
-            self.population.push((state.score, gene));
+            for _ in 0..self.params.mutations_count {
+                self.mutate(&mut new_plan, &mut rng);
+            }
+
+            self.add_plan(simulation, state_proto, drone_idx, &new_plan, Some(idx));
         }
     }
 
-    pub fn search(
+    /// Uniform or single-point crossover along the time axis between two
+    /// plans of the same drone.
+    fn crossover(
         &mut self,
-        world: &World,
-        tracker: &Tracker,
-        exploration_map: &ExplorationMap,
-        score_map: &ScoreMap,
-    ) -> [Action; 2] {
-        let start = Instant::now();
+        simulation: &mut Simulation,
+        state_proto: &GameState,
+        drone_idx: usize,
+    ) {
+        let mut rng = planner_rng();
+
+        let population_size = self.populations[drone_idx].len();
+        if population_size < 2 {
+            return;
+        }
 
-        let state_proto = GameState::new(world);
+        for _ in 0..self.params.crossover_size {
+            let rank1 = tournament(population_size, self.params.tournament_size, &mut rng);
+            let mut rank2 = tournament(population_size, self.params.tournament_size, &mut rng);
 
-        let mut simulation = Simulation::new(tracker, exploration_map, score_map);
+            while rank2 == rank1 {
+                rank2 = rng.gen_range(0..population_size);
+            }
 
-        self.modify_prev_generation(&mut simulation, &state_proto);
-        self.add_straight_top(&mut simulation, &state_proto);
+            let idx1 = self.populations[drone_idx][rank1];
+            let idx2 = self.populations[drone_idx][rank2];
 
-        for _ in 0..POPULATION_SIZE {
-            let mut gene = self.random_gene();
+            let plan1 = self.individuals[idx1].plan;
+            let plan2 = self.individuals[idx2].plan;
 
-            let mut state = state_proto.clone();
-            simulation.simulate_all(&mut state, &mut gene);
-            self.population.push((state.score, gene));
-        }
-
-        let mut iter = 0;
-        while Instant::now().duration_since(start).as_millis() < 40 {
-            iter += 1;
-            self.add_randoms(&mut simulation, &state_proto);
-            self.mutation(&mut simulation, &state_proto);
-            self.crossover(&mut simulation, &state_proto);
-            self.selection();
-        }
+            let mut new_plan1 = plan1;
+            let mut new_plan2 = plan2;
 
-        let (best_score, best_actions) = self.population[0];
+            if rng.gen::<f32>() < self.params.uniform_crossover_rate {
+                for i in 0..GENE_SIZE {
+                    if rng.gen_bool(0.5) {
+                        new_plan1[i] = plan2[i];
+                        new_plan2[i] = plan1[i];
+                    }
+                }
+            } else {
+                let point = rng.gen_range(1..GENE_SIZE);
 
-        eprintln!("Iterations count: {iter}");
-        eprintln!("Best score: {}", best_score.value());
-        eprintln!("Exploration score: {}", best_score.exploration_score);
-        eprintln!("Saving scans score: {}", best_score.saving_scans_score);
-        eprintln!(
-            "Saving urgent scans score: {}",
-            best_score.saving_urgent_scans_score
-        );
-        eprintln!("Ascent score: {}", best_score.ascent_score);
-        eprintln!("Dive score: {}", best_score.dive_score);
-        eprintln!("Dead score: {}", best_score.dead_score);
-        eprintln!("Dead simulations: {}", simulation.dead_simulations);
-        eprintln!("Total simulations: {}", simulation.total_simulations);
+                new_plan1[point..].copy_from_slice(&plan2[point..]);
+                new_plan2[point..].copy_from_slice(&plan1[point..]);
+            }
 
-        best_actions[0]
+            self.add_plan(simulation, state_proto, drone_idx, &new_plan1, Some(idx1));
+            self.add_plan(simulation, state_proto, drone_idx, &new_plan2, Some(idx2));
+        }
     }
-}
 
-impl<'a> Simulation<'a> {
-    pub fn new(
-        tracker: &'a Tracker,
-        exploration_map: &'a ExplorationMap,
-        score_map: &'a ScoreMap,
-    ) -> Self {
-        Simulation {
-            tracker,
-            exploration_map,
-            score_map,
-            dead_simulations: 0,
-            total_simulations: 0,
-        }
-    }
-
-    fn choose_dead_move(&self, state: &mut GameState, drone_idx: usize, action: &mut Action) {
-        let drone = &mut state.drones[drone_idx];
-
-        let base_angle = action.angle;
-
-        let (rot, dist) = [
-            (-PI / 3.),
-            (PI / 3.),
-            (2. * PI / 3.),
-            (-2. * PI / 3.),
-            -PI,
-            (PI / 2.),
-            (-PI / 2.),
-            (-PI / 6.),
-            (PI / 6.),
-            (-5. * PI / 6.),
-            (5. * PI / 6.),
-            (PI / 2.),
-            -PI, // for last iteration happen
-        ]
-        .iter()
-        .map(|&rot| {
-            let dir = Vec2::new(1., 0.).rotate(base_angle + rot);
-            let mov = dir * 600.;
-            let new_pos = (drone.pos + mov).clamp(Vec2::new(0., 0.), Vec2::new(9999., 9999.));
-
-            let nearest_monster = self
-                .tracker
-                .monsters
-                .iter()
-                .min_by_key(|m| (m.pos - new_pos).len() as i32)
-                .unwrap();
+    fn add_randoms(
+        &mut self,
+        simulation: &mut Simulation,
+        state_proto: &GameState,
+        drone_idx: usize,
+    ) {
+        for _ in 0..self.params.random_size {
+            let plan = self.random_plan();
+            self.add_plan(simulation, state_proto, drone_idx, &plan, None);
+        }
+    }
 
-            (rot, (nearest_monster.pos - new_pos).len() as i32)
-        })
-        .max_by_key(|(_, dist)| *dist)
-        .unwrap();
+    fn sort_population(&mut self, drone_idx: usize) {
+        let individuals = &self.individuals;
+        self.populations[drone_idx]
+            .sort_by(|&a, &b| individuals[b].fitness.total_cmp(&individuals[a].fitness));
+    }
 
-        action.angle += rot;
+    /// Keeps the elite and fills the rest of the population by tournaments
+    /// among the remaining unique plans.
+    fn selection(&mut self, simulation: &mut Simulation, drone_idx: usize) {
+        let mut rng = planner_rng();
+
+        self.sort_population(drone_idx);
+        let mut population = self.take_population(drone_idx);
+
+        let mut unique = 0;
+        for i in 0..population.len() {
+            let idx = population[i];
+            if unique > 0
+                && self.individuals[population[unique - 1]].plan == self.individuals[idx].plan
+            {
+                self.release(simulation, idx);
+            } else {
+                population[unique] = idx;
+                unique += 1;
+            }
+        }
+        population.truncate(unique);
+
+        let elite = self
+            .params
+            .elite_size
+            .min(self.params.population_size)
+            .min(population.len());
+        self.populations[drone_idx].extend(population.drain(..elite));
+
+        while self.populations[drone_idx].len() < self.params.population_size
+            && !population.is_empty()
+        {
+            let rank = tournament(population.len(), self.params.tournament_size, &mut rng);
+            self.populations[drone_idx].push(population.remove(rank));
+        }
 
-        state.score.dead_score += (dist / 2) as f32;
+        self.release_population(simulation, population);
+        self.sort_population(drone_idx);
     }
 
-    fn adjust_drone_move(&self, state: &mut GameState, drone_idx: usize, action: &mut Action) {
-        let drone = &mut state.drones[drone_idx];
-
-        let base_angle = action.angle;
+    /// Mean heading difference to the best plan, from 0 for a collapsed
+    /// population to 1 for opposite headings everywhere.
+    fn diversity(&self, drone_idx: usize) -> f32 {
+        let population = &self.populations[drone_idx];
+        let Some(&best) = population.first() else {
+            return 0.;
+        };
+        let best = &self.individuals[best];
+
+        let total = population
+            .iter()
+            .flat_map(|&idx| self.individuals[idx].plan.iter().zip(&best.plan))
+            .map(|(a, b)| {
+                let diff = (a.angle - b.angle).rem_euclid(2. * PI);
+                diff.min(2. * PI - diff) / PI
+            })
+            .sum::<f32>();
+
+        total / (population.len() * GENE_SIZE) as f32
+    }
 
-        for rotation in [
-            (-PI / 3.),
-            (PI / 3.),
-            (2. * PI / 3.),
-            (-2. * PI / 3.),
-            -PI,
-            -PI, // for last iteration happen
-        ] {
-            let dir = Vec2::new(1., 0.).rotate(action.angle);
-            let mov = dir * 600.;
-            let new_pos = (drone.pos + mov).clamp(Vec2::new(0., 0.), Vec2::new(9999., 9999.));
+    /// Scores the population again when the best plans of the other drones
+    /// have changed since the last evaluation.
+    fn refresh(&mut self, simulation: &mut Simulation, state_proto: &GameState, drone_idx: usize) {
+        let partner_ids = self.partner_ids(drone_idx);
 
-            drone.dead = self
-                .tracker
-                .monsters
-                .iter()
-                .any(|m| (m.pos - new_pos).len() < 1200.);
+        if self.partners[drone_idx] == partner_ids {
+            return;
+        }
+        self.partners[drone_idx] = partner_ids;
 
-            if !drone.dead {
-                return;
-            }
+        let old_population = self.take_population(drone_idx);
+        for &parent in &old_population {
+            let plan = self.individuals[parent].plan;
+            let idx = self.score_plan(simulation, state_proto, drone_idx, &plan, Some(parent));
 
-            action.angle = base_angle + rotation;
-            if action.angle > PI {
-                action.angle -= 2. * PI;
+            // An unchanged plan keeps its id, so that rescoring alone doesn't
+            // change the partners of the other populations.
+            if self.individuals[idx].plan == plan {
+                self.individuals[idx].id = self.individuals[parent].id;
             }
+            self.add_individual(drone_idx, idx);
         }
-        self.choose_dead_move(state, drone_idx, action);
+        self.release_population(simulation, old_population);
+        self.selection(simulation, drone_idx);
     }
 
-    fn simulate(&mut self, state: &mut GameState, actions: &mut [Action; 2], iter: usize) {
-        for i in 0..2 {
-            let action = &mut actions[i];
-
-            if state.drones[i].dead {
-                state.score.dead_score -= 1000.;
-                continue;
-            }
+    /// Replaces the plan from the first step that runs into a tracked
+    /// monster with random actions.
+    fn repair_plan(&self, plan: &mut DronePlan, start: Vec2, tracker: &Tracker) {
+        let mut pos = start;
 
-            self.adjust_drone_move(state, i, action);
+        for step in 0..REPAIR_STEPS {
+            pos = (pos + plan[step].get_move()).clamp(Vec2::new(0., 0.), Vec2::new(9999., 9999.));
 
-            if state.drones[i].dead {
-                state.score.dead_score -= 1000.;
-                continue;
+            if tracker.is_dangerous(pos) {
+                for action in &mut plan[step..] {
+                    *action = self.random_action();
+                }
+                return;
             }
+        }
+    }
 
-            let dir = Vec2::new(1., 0.).rotate(action.angle);
-            let mov = dir * 300.;
-
-            for _ in 0..2 {
-                let drone = &mut state.drones[i];
+    /// Carries the populations of the last search over to the drones of
+    /// `ctx` by id. Plans are shifted by one step and repaired, populations
+    /// of missing drones and of drones in emergency are dropped.
+    fn warm_start(&mut self, ctx: &PlanningContext, drone_count: usize) {
+        let drone_ids = &ctx.drone_ids[..drone_count];
+
+        let mut populations = drone_ids
+            .iter()
+            .map(|id| match self.drone_ids.iter().position(|i| i == id) {
+                Some(slot) => std::mem::take(&mut self.populations[slot]),
+                None => Vec::new(),
+            })
+            .collect::<Vec<_>>();
+
+        for (population, id) in populations.iter_mut().zip(drone_ids) {
+            let drone = ctx.world.me.drones.get(id).filter(|d| d.emergency == 0);
+            let Some(drone) = drone else {
+                self.free.append(population);
+                continue;
+            };
 
-                drone.pos = (drone.pos + mov).clamp(Vec2::new(0., 0.), Vec2::new(9999., 9999.));
+            for &idx in population.iter() {
+                let mut plan = self.individuals[idx].plan;
 
-                let (x, y) = position_to_grid_cell(drone.pos, S_CELL_SIZE);
+                plan.rotate_left(1);
+                plan[GENE_SIZE - 1] = self.random_action();
+                self.repair_plan(&mut plan, drone.pos, ctx.tracker);
 
-                state.score.exploration_score += self.exploration_map.get_score_by_idx(x, y)
-                    * self.score_map.get_score_by_idx(x, y)
-                    * state.visit_score(x, y)
-                    / (iter as f32);
-                state.visit_cell(x, y);
+                self.individuals[idx].plan = plan;
             }
+        }
 
-            let drone = &mut state.drones[i];
+        for mut population in std::mem::replace(&mut self.populations, populations) {
+            self.free.append(&mut population);
+        }
+        self.drone_ids = drone_ids.to_vec();
+    }
 
-            if drone.pos.y < 400. {
-                let iter = iter as f32;
+    /// Scores the inherited plans against the updated maps, with the
+    /// inherited plans of the other drones as partners.
+    fn rescore_inherited(
+        &mut self,
+        simulation: &mut Simulation,
+        state_proto: &GameState,
+        drone_idx: usize,
+    ) {
+        let old_population = self.take_population(drone_idx);
+
+        for &idx in &old_population {
+            let plan = self.individuals[idx].plan;
+            self.add_plan(simulation, state_proto, drone_idx, &plan, None);
+        }
+        self.release_population(simulation, old_population);
+    }
 
-                state.score.saving_scans_score += (drone.base_scans_cost) as f32 / iter;
-                state.score.saving_urgent_scans_score +=
-                    (drone.urgent_scans_cost * drone.urgent_scans_cost) as f32 / iter / iter;
-                drone.base_scans_cost = 0;
-                drone.urgent_scans_cost = 0;
-            }
+    pub fn search(&mut self, ctx: &PlanningContext, budget: Duration) -> Plan {
+        let start = Instant::now();
 
-            let drone = &state.drones[i];
+        let state_proto = GameState::new(ctx);
 
-            let (x, y) = position_to_grid_cell(drone.pos, S_CELL_SIZE);
-            let (x, y) = (x as i32, y as i32);
+        let params = self.params;
+        let mut simulation = Simulation::new(ctx, &params);
 
-            let mut light_score = 0.;
+        if self.params.coevolution {
+            self.shift_opponents();
+        }
 
-            if drone.bat >= 5 {
-                for dx in -1..2 {
-                    for dy in -1..2 {
-                        if x + dx >= 0
-                            && x + dx < S_CELLS as i32
-                            && y + dy >= 0
-                            && y + dy < S_CELLS as i32
-                        {
-                            let (x, y) = ((x + dx) as usize, (y + dy) as usize);
+        // Checkpoints of the previous search belong to its simulation.
+        for individual in &mut self.individuals {
+            individual.checkpoints.clear();
+        }
 
-                            light_score += self.exploration_map.get_score_by_idx(x, y)
-                                * self.score_map.get_score_by_idx(x, y)
-                                * state.visit_score(x, y);
-                        }
-                    }
-                }
-                if light_score > 0.5 {
-                    action.light = true;
-                } else {
-                    action.light = false;
-                }
-            } else {
-                action.light = false;
-            }
+        let drone_count = state_proto.drone_count;
+        self.warm_start(ctx, drone_count);
+        self.best = None;
+        self.partners = vec![Vec::new(); drone_count];
 
-            if action.light {
-                for dx in -1..2 {
-                    for dy in -1..2 {
-                        if x + dx >= 0
-                            && x + dx < S_CELLS as i32
-                            && y + dy >= 0
-                            && y + dy < S_CELLS as i32
-                        {
-                            let (x, y) = ((x + dx) as usize, (y + dy) as usize);
-                            state.visit_cell(x, y);
-                        }
-                    }
-                }
+        for drone_idx in 0..drone_count {
+            self.rescore_inherited(&mut simulation, &state_proto, drone_idx);
+        }
 
-                state.score.exploration_score += light_score / (iter as f32);
+        let field = potential_field_gene(ctx, &self.params);
+
+        for drone_idx in 0..drone_count {
+            self.add_straight_top(&mut simulation, &state_proto, drone_idx);
+            self.add_plan(
+                &mut simulation,
+                &state_proto,
+                drone_idx,
+                &split(&field, drone_idx),
+                None,
+            );
+
+            for _ in 0..self.params.population_size {
+                let plan = self.random_plan();
+                self.add_plan(&mut simulation, &state_proto, drone_idx, &plan, None);
             }
 
-            let drone = &mut state.drones[i];
+            self.selection(&mut simulation, drone_idx);
+        }
 
-            if action.light {
-                drone.bat -= 5;
-            } else {
-                drone.bat += 1;
-            }
+        let mut iter = 0;
+        while Instant::now().duration_since(start) < budget
+            && (self.params.max_generations == 0 || iter < self.params.max_generations)
+        {
+            iter += 1;
 
-            state.score.ascent_score +=
-                drone.base_scans_cost as f32 * (1. - drone.pos.y / 10000.) / 5. / GENE_SIZE as f32;
+            for drone_idx in 0..drone_count {
+                self.refresh(&mut simulation, &state_proto, drone_idx);
+                self.add_randoms(&mut simulation, &state_proto, drone_idx);
+                self.mutation(&mut simulation, &state_proto, drone_idx);
+                self.crossover(&mut simulation, &state_proto, drone_idx);
+                self.selection(&mut simulation, drone_idx);
+            }
 
-            if drone.base_scans_cost == 0 {
-                state.score.dive_score += drone.pos.y / 10000. / 5 as f32;
+            if self.params.coevolution {
+                self.evolve_opponents(&mut simulation, &state_proto);
             }
         }
-    }
 
-    fn simulate_all(&mut self, state: &mut GameState, gene: &mut Gene) {
-        for (iter, action) in gene.iter_mut().enumerate() {
-            self.simulate(state, action, iter + 1);
+        let (best_score, best_gene) = self.best.unwrap_or_default();
+
+        let report = SearchReport {
+            generations: iter,
+            simulations: simulation.total_simulations,
+            dead_simulations: simulation.dead_simulations,
+            reused_steps: simulation.reused_steps,
+            simulated_steps: simulation.simulated_steps,
+            score: best_score,
+            drone_scores: self
+                .populations
+                .iter()
+                .map(|population| {
+                    population
+                        .first()
+                        .map_or_else(Score::default, |&idx| self.individuals[idx].score)
+                })
+                .collect(),
+            diversity: (0..drone_count).map(|i| self.diversity(i)).collect(),
+            elapsed: start.elapsed(),
+        };
+
+        Plan {
+            actions: best_gene[0],
+            gene: best_gene,
+            score: best_score,
+            report,
         }
+    }
+}
 
-        self.total_simulations += 1;
+impl Planner for Pathfinding {
+    fn plan(&mut self, ctx: &PlanningContext, budget: Duration) -> Plan {
+        self.search(ctx, budget)
+    }
 
-        if state.drones[0].dead || state.drones[1].dead {
-            self.dead_simulations += 1;
-        }
+    fn candidates(&self) -> Vec<Gene> {
+        let ranks = self.populations.iter().map(Vec::len).min().unwrap_or(0);
+
+        // Plans of the same rank in every population.
+        let paired = (0..ranks).map(|rank| {
+            let mut gene = Gene::default();
+            for (drone_idx, population) in self.populations.iter().enumerate() {
+                set_plan(
+                    &mut gene,
+                    drone_idx,
+                    &self.individuals[population[rank]].plan,
+                );
+            }
+            gene
+        });
+
+        self.best
+            .iter()
+            .map(|(_, gene)| *gene)
+            .chain(paired)
+            .collect()
     }
 }
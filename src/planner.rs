@@ -0,0 +1,155 @@
+use std::{fmt::Display, time::Duration};
+
+use super::*;
+
+pub struct PlanningContext<'a> {
+    pub world: &'a World,
+    pub tracker: &'a Tracker,
+    pub bounds_detector: &'a BoundsDetector,
+    pub exploration_map: &'a ExplorationMap,
+    pub score_map: &'a ScoreMap,
+    /// Score maps biased to the fish allocated to each drone, by plan slot.
+    pub drone_score_maps: &'a [ScoreMap],
+    /// Lights each drone may use per depth band over its next dive.
+    pub battery: &'a BatteryPlan,
+    /// Ids of our drones by plan slot, fixed on the first turn.
+    pub drone_ids: &'a [i32],
+}
+
+/// Statistics of one search, for logging and the visualizer.
+#[derive(Clone, Default, Debug)]
+pub struct SearchReport {
+    /// Generations, beam depth or tree iterations, depending on the planner.
+    pub generations: usize,
+    /// Simulated plans, or expanded nodes for beam search.
+    pub simulations: i32,
+    pub dead_simulations: i32,
+    /// Steps skipped by resuming from cached game states.
+    pub reused_steps: i32,
+    /// Steps simulated from scratch, without the reused ones.
+    pub simulated_steps: i32,
+    /// Score of the chosen plan.
+    pub score: Score,
+    /// Best score of every drone's population, empty for planners that
+    /// search joint plans.
+    pub drone_scores: Vec<Score>,
+    /// Population diversity of every drone, see `Pathfinding`.
+    pub diversity: Vec<f32>,
+    pub elapsed: Duration,
+}
+
+impl SearchReport {
+    pub fn simulations_per_ms(&self) -> f32 {
+        self.simulations as f32 / self.elapsed_ms()
+    }
+
+    /// Full plans per millisecond the simulated steps amount to, the rate
+    /// without checkpoints.
+    pub fn uncached_per_ms(&self) -> f32 {
+        self.simulated_steps as f32 / GENE_SIZE as f32 / self.elapsed_ms()
+    }
+
+    fn elapsed_ms(&self) -> f32 {
+        (self.elapsed.as_secs_f32() * 1000.).max(f32::EPSILON)
+    }
+}
+
+impl Display for SearchReport {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let diversity = self
+            .diversity
+            .iter()
+            .map(|d| format!("{d:.2}"))
+            .collect::<Vec<_>>();
+
+        write!(
+            f,
+            "gen {} sims {} dead {} ({:.0}/ms, {:.0}/ms uncached) score {:.2} diversity [{}] {}ms",
+            self.generations,
+            self.simulations,
+            self.dead_simulations,
+            self.simulations_per_ms(),
+            self.uncached_per_ms(),
+            self.score.value(),
+            diversity.join(" "),
+            self.elapsed.as_millis()
+        )
+    }
+}
+
+/// Search statistics summed over the turns of a game.
+#[derive(Clone, Default, Debug)]
+pub struct GameReport {
+    pub turns: usize,
+    pub simulations: i64,
+    pub dead_simulations: i64,
+    pub simulated_steps: i64,
+    pub elapsed: Duration,
+    /// Slowest turn, the one closest to a timeout.
+    pub max_elapsed: Duration,
+}
+
+impl GameReport {
+    pub fn add(&mut self, report: &SearchReport) {
+        self.turns += 1;
+        self.simulations += report.simulations as i64;
+        self.dead_simulations += report.dead_simulations as i64;
+        self.simulated_steps += report.simulated_steps as i64;
+        self.elapsed += report.elapsed;
+        self.max_elapsed = self.max_elapsed.max(report.elapsed);
+    }
+
+    fn elapsed_ms(&self) -> f32 {
+        (self.elapsed.as_secs_f32() * 1000.).max(f32::EPSILON)
+    }
+}
+
+impl Display for GameReport {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "game: turns {} sims {} dead {} ({:.0}/ms, {:.0}/ms uncached) max {}ms",
+            self.turns,
+            self.simulations,
+            self.dead_simulations,
+            self.simulations as f32 / self.elapsed_ms(),
+            self.simulated_steps as f32 / GENE_SIZE as f32 / self.elapsed_ms(),
+            self.max_elapsed.as_millis()
+        )
+    }
+}
+
+pub struct Plan {
+    pub actions: Actions,
+    pub gene: Gene,
+    pub score: Score,
+    pub report: SearchReport,
+}
+
+pub trait Planner {
+    fn plan(&mut self, ctx: &PlanningContext, budget: Duration) -> Plan;
+
+    /// Plans considered during the last search, best first. Used for drawing.
+    fn candidates(&self) -> Vec<Gene> {
+        Vec::new()
+    }
+}
+
+pub fn new_planner(params: StrategyParams) -> Box<dyn Planner> {
+    match params.planner {
+        PlannerKind::Genetic => Box::new(Pathfinding::new(params)),
+        PlannerKind::Beam => Box::new(BeamSearch::new(params)),
+        PlannerKind::Mcts => Box::new(Mcts::new(params)),
+    }
+}
+
+/// Score of `gene` in a fresh simulation. Fish positions are sampled from
+/// their bounds, so offline tools average it over several calls.
+pub fn score_gene(ctx: &PlanningContext, params: &StrategyParams, gene: &Gene) -> Score {
+    let mut simulation = Simulation::new(ctx, params);
+    let mut state = GameState::new(ctx);
+    let mut gene = *gene;
+
+    simulation.simulate_all(&mut state, &mut gene);
+    state.score
+}
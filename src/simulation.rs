@@ -0,0 +1,895 @@
+use std::f32::consts::PI;
+
+use rand::Rng;
+
+use super::*;
+
+#[derive(Clone, Copy, PartialEq)]
+pub struct Action {
+    pub(crate) angle: f32,
+    pub(crate) light: bool,
+    /// Fraction of the full move, zero is `WAIT`.
+    pub(crate) thrust: f32,
+}
+
+impl Default for Action {
+    fn default() -> Self {
+        Action {
+            angle: 0.,
+            light: false,
+            thrust: 1.,
+        }
+    }
+}
+
+pub(crate) const DRONE_SPEED: f32 = 600.;
+const SINK_SPEED: f32 = 300.;
+
+impl Action {
+    pub fn get_light(&self) -> bool {
+        self.light
+    }
+
+    pub fn is_wait(&self) -> bool {
+        self.thrust <= 0.
+    }
+
+    /// Displacement over the turn, a waiting drone sinks.
+    pub fn get_move(&self) -> Vec2 {
+        if self.is_wait() {
+            Vec2::new(0., SINK_SPEED)
+        } else {
+            Vec2::new(1., 0.).rotate(self.angle) * DRONE_SPEED * self.thrust.min(1.)
+        }
+    }
+
+    /// Options of the tree planners: `angles` headings at full thrust, with
+    /// and without light. The thrust encoding adds half moves and `WAIT`.
+    pub(crate) fn discrete(angles: usize, encoding: GeneEncoding) -> Vec<Action> {
+        let thrusts: &[f32] = match encoding {
+            GeneEncoding::Angle => &[1.],
+            GeneEncoding::Thrust => &[1., 0.5],
+        };
+
+        let moves = (0..angles.max(1)).flat_map(|i| {
+            let angle = -PI + 2. * PI * i as f32 / angles.max(1) as f32;
+            thrusts.iter().map(move |&thrust| (angle, thrust))
+        });
+        let wait = (encoding == GeneEncoding::Thrust).then_some((0., 0.));
+
+        moves
+            .chain(wait)
+            .flat_map(|(angle, thrust)| {
+                [false, true].map(|light| Action {
+                    angle,
+                    light,
+                    thrust,
+                })
+            })
+            .collect()
+    }
+}
+
+#[derive(Default, Clone, Copy)]
+pub(crate) struct DroneState {
+    pos: Vec2,
+    bat: i32,
+    emergency_turns: i32,
+    caught: bool,
+    base_scans_cost: i32,
+    urgent_scans_cost: i32,
+    /// Carried fish whose first-save bonus is still up for grabs.
+    race_scans: u64,
+    /// The drone is not in the current turn input.
+    missing: bool,
+    /// Lights used in each depth band during the simulation.
+    band_lights: [u8; DEPTH_BANDS],
+}
+
+#[derive(Clone, Copy, Default, Debug)]
+pub struct Score {
+    pub saving_scans_score: f32,
+    pub saving_urgent_scans_score: f32,
+    pub exploration_score: f32,
+    pub scan_risk_score: f32,
+    pub dive_score: f32,
+    pub dead_score: f32,
+    pub lost_scans_score: f32,
+    pub race_score: f32,
+    pub aggression_score: f32,
+    pub overlap_score: f32,
+    pub combo_score: f32,
+}
+
+impl Score {
+    pub fn value(&self) -> f32 {
+        self.saving_scans_score
+            + self.saving_urgent_scans_score
+            + self.exploration_score
+            + self.dead_score
+            + self.lost_scans_score
+            + self.scan_risk_score
+            + self.dive_score
+            + self.race_score
+            + self.aggression_score
+            + self.overlap_score
+            + self.combo_score
+    }
+}
+
+const CELL_WORDS: usize = (S_CELLS * S_CELLS).div_ceil(64);
+
+/// Bitset over the grid cells.
+#[derive(Clone, Copy, Default)]
+struct CellSet([u64; CELL_WORDS]);
+
+impl CellSet {
+    fn contains(&self, x: usize, y: usize) -> bool {
+        let bit = x * S_CELLS + y;
+        self.0[bit / 64] & 1 << (bit % 64) != 0
+    }
+
+    fn insert(&mut self, x: usize, y: usize) {
+        let bit = x * S_CELLS + y;
+        self.0[bit / 64] |= 1 << (bit % 64);
+    }
+}
+
+#[derive(Clone)]
+pub(crate) struct GameState {
+    pub(crate) drones: [DroneState; MAX_DRONES],
+    pub(crate) drone_count: usize,
+    opponents: [DroneState; MAX_DRONES],
+    opponent_count: usize,
+    fish_costs: [u8; MAX_CREATURE_ID],
+    /// Cells each of our drones went through.
+    visited: [CellSet; MAX_DRONES],
+    /// Fish scanned during the simulation, by creature id.
+    scanned: u64,
+    /// Drone slots that have saved their scans.
+    surfaced: u8,
+    /// Combo goals already rewarded or missed.
+    combos_done: u32,
+    pub(crate) score: Score,
+    iter: i32,
+}
+
+const EMERGENCY_SPEED: f32 = 300.;
+const MAX_CREATURE_ID: usize = 64;
+pub(crate) const LIGHT_COST: i32 = 5;
+const MAX_BATTERY: i32 = 30;
+pub(crate) const LIGHT_RADIUS: f32 = 800.;
+pub(crate) const POWERED_LIGHT_RADIUS: f32 = 2000.;
+
+/// Turns an emergency drone needs to float up to the surface, where it gets
+/// repaired.
+fn emergency_turns(pos: Vec2) -> i32 {
+    ((pos.y / EMERGENCY_SPEED).ceil() as i32).max(1)
+}
+
+fn estimate_drones_scans_profit(world: &World, drone: &Drone, state: &mut DroneState) {
+    let mut by_typ_count = [0; 3];
+    let mut by_color_count = [0; 4];
+
+    for id in &world.me.scans {
+        let creature = world.creatures.get(&id).unwrap();
+        by_typ_count[creature.typ as usize] += 1;
+        by_color_count[creature.color as usize] += 1;
+    }
+
+    for id in &drone.scans {
+        let creature = world.creatures.get(&id).unwrap();
+        let fish_cost = (creature.typ + 1) as i32;
+        state.base_scans_cost += fish_cost;
+
+        if !world.opponent.scans.contains(&id) {
+            state.urgent_scans_cost += fish_cost;
+        }
+
+        by_typ_count[creature.typ as usize] += 1;
+        by_color_count[creature.color as usize] += 1;
+
+        if by_typ_count[creature.typ as usize] == 4 {
+            state.base_scans_cost += 4;
+
+            let opponents_count = world
+                .opponent
+                .scans
+                .iter()
+                .filter(|id| world.creatures.get(&id).unwrap().typ == creature.typ)
+                .count();
+            if opponents_count < 4 {
+                state.urgent_scans_cost += 4;
+            }
+        }
+
+        if by_color_count[creature.color as usize] == 3 {
+            state.base_scans_cost += 3;
+
+            let opponents_count = world
+                .opponent
+                .scans
+                .iter()
+                .filter(|id| world.creatures.get(&id).unwrap().color == creature.color)
+                .count();
+            if opponents_count < 3 {
+                state.urgent_scans_cost += 3;
+            }
+        }
+    }
+}
+
+/// Fish carried by `drone` that neither player has saved yet, as a bitmask
+/// over creature ids.
+fn race_scans(world: &World, drone: &Drone) -> u64 {
+    drone
+        .scans
+        .iter()
+        .filter(|&&id| (id as usize) < MAX_CREATURE_ID)
+        .filter(|id| !world.me.scans.contains(id) && !world.opponent.scans.contains(id))
+        .fold(0, |mask, &id| mask | 1 << id)
+}
+
+impl GameState {
+    /// Our drones are laid out in the slots fixed by `ctx.drone_ids`.
+    pub(crate) fn new(ctx: &PlanningContext) -> Self {
+        let world = ctx.world;
+
+        let mut drones = [DroneState::default(); MAX_DRONES];
+        for (state, id) in drones.iter_mut().zip(ctx.drone_ids) {
+            let Some(drone) = world.me.drones.get(id) else {
+                state.missing = true;
+                continue;
+            };
+
+            state.pos = drone.pos;
+            state.bat = drone.bat;
+            if drone.emergency == 1 {
+                state.emergency_turns = emergency_turns(drone.pos);
+            }
+            estimate_drones_scans_profit(world, drone, state);
+            state.race_scans = race_scans(world, drone);
+        }
+
+        let mut opponents = [DroneState::default(); MAX_DRONES];
+        for (i, drone) in world.opponent.drones.values().take(MAX_DRONES).enumerate() {
+            opponents[i].pos = drone.pos;
+            opponents[i].bat = drone.bat;
+            if drone.emergency == 1 {
+                opponents[i].emergency_turns = emergency_turns(drone.pos);
+            }
+            opponents[i].race_scans = race_scans(world, drone);
+        }
+
+        let mut fish_costs = [0; MAX_CREATURE_ID];
+        for c in world.creatures.values() {
+            if c.typ != -1 && (c.id as usize) < MAX_CREATURE_ID {
+                fish_costs[c.id as usize] = (c.typ + 1) as u8;
+            }
+        }
+
+        GameState {
+            drones,
+            drone_count: ctx.drone_ids.len().min(MAX_DRONES),
+            opponents,
+            opponent_count: world.opponent.drones.len().min(MAX_DRONES),
+            fish_costs,
+            visited: [CellSet::default(); MAX_DRONES],
+            scanned: 0,
+            surfaced: 0,
+            combos_done: 0,
+            score: Score::default(),
+            iter: world.iter,
+        }
+    }
+
+    fn any_caught(&self) -> bool {
+        self.drones[..self.drone_count].iter().any(|d| d.caught)
+    }
+
+    fn visit_score(&self, x: usize, y: usize, params: &StrategyParams) -> f32 {
+        if !self.visited.iter().any(|cells| cells.contains(x, y)) {
+            1.
+        } else {
+            params.visited_score
+        }
+    }
+
+    fn visit_cell(&mut self, x: usize, y: usize, drone_idx: usize) {
+        self.visited[drone_idx].insert(x, y);
+    }
+
+    fn visited_by_partner(&self, x: usize, y: usize, drone_idx: usize) -> bool {
+        self.visited
+            .iter()
+            .enumerate()
+            .any(|(i, cells)| i != drone_idx && cells.contains(x, y))
+    }
+}
+
+fn scans_cost(fish_costs: &[u8; MAX_CREATURE_ID], mask: u64) -> i32 {
+    (0..MAX_CREATURE_ID)
+        .filter(|id| mask & 1 << id != 0)
+        .map(|id| fish_costs[id] as i32)
+        .sum()
+}
+
+pub const GENE_SIZE: usize = 25;
+
+/// Drones per player the planners can control. The game gives every player
+/// two, so plans are capped there to keep genes small: drones past the cap
+/// get `WAIT` every turn, and `Strategy` warns when that happens.
+pub const MAX_DRONES: usize = 2;
+
+/// One action per drone slot, slots past the drone count are ignored.
+pub type Actions = [Action; MAX_DRONES];
+
+pub type Gene = [Actions; GENE_SIZE];
+
+/// Fish our drones can still scan, at a position sampled from its bounds.
+struct SimulatedFish {
+    id: usize,
+    pos: Vec2,
+    cost: i32,
+    /// Nobody has saved it yet, so the first-save bonus is available.
+    urgent: bool,
+}
+
+/// Samples a position for every fish that is still in the game and that
+/// none of our drones has scanned.
+fn sample_fish(world: &World, bounds_detector: &BoundsDetector) -> Vec<SimulatedFish> {
+    let mut rng = planner_rng();
+
+    world
+        .creatures
+        .values()
+        .filter(|c| c.typ != -1 && (c.id as usize) < MAX_CREATURE_ID)
+        .filter(|c| !world.me.scans.contains(&c.id))
+        .filter(|c| {
+            world
+                .me
+                .drones
+                .values()
+                .all(|drone| !drone.scans.contains(&c.id))
+        })
+        .filter(|c| {
+            world
+                .me
+                .drones
+                .values()
+                .any(|drone| drone.blips.contains_key(&c.id))
+        })
+        .map(|c| {
+            let pos = c.pos.unwrap_or_else(|| {
+                let bounds = bounds_detector.get_bounds(c.id);
+                let (min, max) = (bounds.top_left, bounds.bot_right);
+                Vec2::new(
+                    min.x + (max.x - min.x) * rng.gen::<f32>(),
+                    min.y + (max.y - min.y) * rng.gen::<f32>(),
+                )
+            });
+            SimulatedFish {
+                id: c.id as usize,
+                pos,
+                cost: (c.typ + 1) as i32,
+                urgent: !world.opponent.scans.contains(&c.id),
+            }
+        })
+        .collect()
+}
+
+/// Handle of a game state in a `StatePool`.
+pub(crate) type StateHandle = u32;
+
+/// Reference counted game states, released slots are reused so that the
+/// search doesn't allocate once the pool has grown.
+#[derive(Default)]
+pub(crate) struct StatePool {
+    states: Vec<GameState>,
+    refs: Vec<u32>,
+    free: Vec<StateHandle>,
+}
+
+impl StatePool {
+    fn insert(&mut self, state: &GameState) -> StateHandle {
+        match self.free.pop() {
+            Some(handle) => {
+                self.states[handle as usize] = state.clone();
+                self.refs[handle as usize] = 1;
+                handle
+            }
+            None => {
+                self.states.push(state.clone());
+                self.refs.push(1);
+                (self.states.len() - 1) as StateHandle
+            }
+        }
+    }
+
+    fn get(&self, handle: StateHandle) -> &GameState {
+        &self.states[handle as usize]
+    }
+
+    fn retain(&mut self, handle: StateHandle) {
+        self.refs[handle as usize] += 1;
+    }
+
+    pub(crate) fn release(&mut self, handle: StateHandle) {
+        self.refs[handle as usize] -= 1;
+        if self.refs[handle as usize] == 0 {
+            self.free.push(handle);
+        }
+    }
+}
+
+pub(crate) struct Simulation<'a> {
+    tracker: &'a Tracker,
+    /// `ExplorationMap` resampled to the cells of the score maps.
+    exploration: ScoreGrid,
+    score_map: &'a ScoreMap,
+    drone_score_maps: &'a [ScoreMap],
+    battery: &'a BatteryPlan,
+    params: &'a StrategyParams,
+    fish: Vec<SimulatedFish>,
+    combo_goals: Vec<ComboGoal>,
+    pub(crate) dead_simulations: i32,
+    pub(crate) total_simulations: i32,
+    /// Steps skipped by resuming from a checkpoint.
+    pub(crate) reused_steps: i32,
+    /// Steps actually simulated, by any caller.
+    pub(crate) simulated_steps: i32,
+    /// Replaces the light bits with the local heuristic while simulating.
+    pub(crate) repair_light: bool,
+    /// Checkpoints of `simulate_cached`, valid for this simulation only.
+    pub(crate) states: StatePool,
+}
+
+impl<'a> Simulation<'a> {
+    pub(crate) fn new(ctx: &PlanningContext<'a>, params: &'a StrategyParams) -> Self {
+        Simulation {
+            tracker: ctx.tracker,
+            exploration: ctx.exploration_map.on_score_grid(),
+            score_map: ctx.score_map,
+            drone_score_maps: ctx.drone_score_maps,
+            battery: ctx.battery,
+            params,
+            fish: sample_fish(ctx.world, ctx.bounds_detector),
+            combo_goals: combo_goals(ctx.world, ctx.drone_ids),
+            dead_simulations: 0,
+            total_simulations: 0,
+            reused_steps: 0,
+            simulated_steps: 0,
+            repair_light: false,
+            states: StatePool::default(),
+        }
+    }
+
+    fn choose_dead_move(&self, state: &mut GameState, drone_idx: usize, action: &mut Action) {
+        let drone = &mut state.drones[drone_idx];
+
+        let base_angle = action.angle;
+
+        let (rot, dist) = [
+            (-PI / 3.),
+            (PI / 3.),
+            (2. * PI / 3.),
+            (-2. * PI / 3.),
+            -PI,
+            (PI / 2.),
+            (-PI / 2.),
+            (-PI / 6.),
+            (PI / 6.),
+            (-5. * PI / 6.),
+            (5. * PI / 6.),
+            (PI / 2.),
+            -PI, // for last iteration happen
+        ]
+        .iter()
+        .map(|&rot| {
+            let dir = Vec2::new(1., 0.).rotate(base_angle + rot);
+            let mov = dir * 600.;
+            let new_pos = (drone.pos + mov).clamp(Vec2::new(0., 0.), Vec2::new(9999., 9999.));
+
+            let nearest_monster = self
+                .tracker
+                .monsters
+                .iter()
+                .min_by_key(|m| (m.pos - new_pos).len() as i32)
+                .unwrap();
+
+            (rot, (nearest_monster.pos - new_pos).len() as i32)
+        })
+        .max_by_key(|(_, dist)| *dist)
+        .unwrap();
+
+        action.angle += rot;
+
+        state.score.dead_score += (dist / 2) as f32;
+    }
+
+    /// Turns the action away from monsters. Returns false if every direction
+    /// leads into one.
+    fn adjust_drone_move(
+        &self,
+        state: &mut GameState,
+        drone_idx: usize,
+        action: &mut Action,
+    ) -> bool {
+        let drone = &mut state.drones[drone_idx];
+
+        let base_angle = action.angle;
+
+        for rotation in [
+            (-PI / 3.),
+            (PI / 3.),
+            (2. * PI / 3.),
+            (-2. * PI / 3.),
+            -PI,
+            -PI, // for last iteration happen
+        ] {
+            let new_pos =
+                (drone.pos + action.get_move()).clamp(Vec2::new(0., 0.), Vec2::new(9999., 9999.));
+
+            if !self.tracker.is_dangerous(new_pos) {
+                return true;
+            }
+
+            // Escape at full speed.
+            action.thrust = 1.;
+            action.angle = base_angle + rotation;
+            if action.angle > PI {
+                action.angle -= 2. * PI;
+            }
+        }
+        self.choose_dead_move(state, drone_idx, action);
+        false
+    }
+
+    /// Emergency mode drops the unsaved scans and disables the drone until it
+    /// floats back to the surface.
+    fn enter_emergency(&self, state: &mut GameState, drone_idx: usize, iter: usize) {
+        let drone = &mut state.drones[drone_idx];
+
+        drone.caught = true;
+        drone.emergency_turns = emergency_turns(drone.pos);
+
+        state.score.lost_scans_score -=
+            drone.base_scans_cost as f32 * self.params.lost_scans_weight;
+        state.score.dead_score -= self.params.dead_penalty * (GENE_SIZE + 1 - iter) as f32;
+
+        drone.base_scans_cost = 0;
+        drone.urgent_scans_cost = 0;
+    }
+
+    fn float_up(drone: &mut DroneState) {
+        drone.pos.y = (drone.pos.y - EMERGENCY_SPEED).max(0.);
+        drone.emergency_turns -= 1;
+    }
+
+    /// Scans the sampled fish inside the light radius of the drone.
+    fn scan_fish(&self, state: &mut GameState, drone_idx: usize, light: bool) {
+        let radius = if light {
+            POWERED_LIGHT_RADIUS
+        } else {
+            LIGHT_RADIUS
+        };
+
+        let drone = &mut state.drones[drone_idx];
+
+        for fish in &self.fish {
+            if state.scanned & 1 << fish.id != 0 || (fish.pos - drone.pos).len() > radius {
+                continue;
+            }
+
+            state.scanned |= 1 << fish.id;
+            drone.base_scans_cost += fish.cost;
+            if fish.urgent {
+                drone.urgent_scans_cost += fish.cost;
+                drone.race_scans |= 1 << fish.id;
+            }
+        }
+    }
+
+    fn score_map(&self, drone_idx: usize) -> &ScoreMap {
+        self.drone_score_maps
+            .get(drone_idx)
+            .unwrap_or(self.score_map)
+    }
+
+    /// Whether the battery plan leaves a light to the drone in `band`.
+    fn light_budgeted(&self, drone: &DroneState, drone_idx: usize, band: usize) -> bool {
+        !self.params.battery_planner
+            || (drone.band_lights[band] as usize) < self.battery.lights(drone_idx, band)
+    }
+
+    /// Unexplored value the powered light would reveal around `pos`.
+    fn light_score(&self, state: &GameState, drone_idx: usize, pos: Vec2) -> f32 {
+        let score_map = self.score_map(drone_idx);
+
+        ScoreGrid::circle(pos, POWERED_LIGHT_RADIUS)
+            .map(|(x, y)| {
+                self.exploration[(x, y)]
+                    * score_map.get_score_by_idx(x, y)
+                    * state.visit_score(x, y, self.params)
+            })
+            .sum()
+    }
+
+    /// Closeness of the monsters to `pos` at step `iter` of the plan.
+    fn monster_danger(&self, pos: Vec2, iter: usize) -> f32 {
+        self.tracker
+            .monsters
+            .iter()
+            .map(|m| (1. - (m.pos_at(iter) - pos).len() / self.params.danger_radius).max(0.))
+            .sum()
+    }
+
+    /// Expected loss of the carried scans: emergency mode drops them, and the
+    /// chance to get caught grows with depth and with monsters around.
+    fn scans_at_risk(&self, drone: &DroneState, iter: usize) -> f32 {
+        let depth = drone.pos.y / 10000.;
+        let danger = self.params.depth_risk + self.monster_danger(drone.pos, iter);
+
+        drone.base_scans_cost as f32 * depth * danger * self.params.scan_risk_weight
+    }
+
+    pub(crate) fn simulate(&mut self, state: &mut GameState, actions: &mut Actions, iter: usize) {
+        self.simulated_steps += 1;
+
+        for (i, action) in actions.iter_mut().enumerate().take(state.drone_count) {
+            if state.drones[i].missing {
+                continue;
+            }
+
+            if state.drones[i].emergency_turns > 0 {
+                Self::float_up(&mut state.drones[i]);
+                continue;
+            }
+
+            if !self.adjust_drone_move(state, i, action) {
+                self.enter_emergency(state, i, iter);
+                continue;
+            }
+
+            let mov = action.get_move() * 0.5;
+
+            for _ in 0..2 {
+                let drone = &mut state.drones[i];
+
+                drone.pos = (drone.pos + mov).clamp(Vec2::new(0., 0.), Vec2::new(9999., 9999.));
+
+                let (x, y) = ScoreGrid::cell(drone.pos);
+
+                let value = self.exploration[(x, y)] * self.score_map(i).get_score_by_idx(x, y);
+
+                state.score.exploration_score +=
+                    value * state.visit_score(x, y, self.params) / (iter as f32);
+
+                if state.visited_by_partner(x, y, i) {
+                    state.score.overlap_score -= value * self.params.overlap_weight / (iter as f32);
+                }
+                state.visit_cell(x, y, i);
+            }
+
+            let drone = &mut state.drones[i];
+
+            if drone.pos.y < 400. {
+                let iter = iter as f32;
+
+                state.score.saving_scans_score += (drone.base_scans_cost) as f32 / iter;
+                state.score.saving_urgent_scans_score +=
+                    (drone.urgent_scans_cost * drone.urgent_scans_cost) as f32 / iter / iter;
+                drone.base_scans_cost = 0;
+                drone.urgent_scans_cost = 0;
+                state.surfaced |= 1 << i;
+
+                // Saved first, the opponent can't take these bonuses anymore.
+                let saved = drone.race_scans;
+                for drone in &mut state.drones {
+                    drone.race_scans &= !saved;
+                }
+            }
+
+            let (pos, bat) = (state.drones[i].pos, state.drones[i].bat);
+            let band = depth_band(pos.y);
+            let can_light = bat >= LIGHT_COST && self.light_budgeted(&state.drones[i], i, band);
+
+            let light_score = if can_light && (action.light || self.repair_light) {
+                self.light_score(state, i, pos)
+            } else {
+                0.
+            };
+
+            if !can_light {
+                action.light = false;
+            } else if self.repair_light {
+                action.light = light_score > self.params.light_threshold;
+            }
+
+            if action.light {
+                for (x, y) in ScoreGrid::circle(pos, POWERED_LIGHT_RADIUS) {
+                    state.visit_cell(x, y, i);
+                }
+
+                state.score.exploration_score += light_score / (iter as f32);
+            }
+
+            self.scan_fish(state, i, action.light);
+
+            let drone = &mut state.drones[i];
+
+            if action.light {
+                drone.bat -= LIGHT_COST;
+                drone.band_lights[band] += 1;
+            } else {
+                drone.bat = (drone.bat + 1).min(MAX_BATTERY);
+            }
+
+            state.score.scan_risk_score -= self.scans_at_risk(drone, iter) / GENE_SIZE as f32;
+
+            if drone.base_scans_cost == 0 {
+                state.score.dive_score += drone.pos.y / 10000. / self.params.dive_divisor;
+            }
+        }
+
+        self.score_combos(state, iter);
+    }
+
+    /// Rewards the combo goals whose drones have all surfaced, and penalizes
+    /// the ones that missed their deadline.
+    fn score_combos(&self, state: &mut GameState, iter: usize) {
+        for (i, goal) in self.combo_goals.iter().enumerate() {
+            if state.combos_done & 1 << i != 0 {
+                continue;
+            }
+
+            let value = goal.bonus as f32 * self.params.combo_weight / iter as f32;
+
+            if state.surfaced & goal.drones == goal.drones {
+                state.score.combo_score += value;
+            } else if iter >= goal.deadline {
+                state.score.combo_score -= value;
+            } else {
+                continue;
+            }
+            state.combos_done |= 1 << i;
+        }
+    }
+
+    /// Moves the opponent drones along their plan. They race us for the
+    /// first-save bonuses. Monsters are not retargeted by their lights, the
+    /// aggression is only a penalty for our drones near the lit monsters.
+    pub(crate) fn simulate_opponents(
+        &mut self,
+        state: &mut GameState,
+        actions: &Actions,
+        iter: usize,
+    ) {
+        for (i, action) in actions.iter().enumerate().take(state.opponent_count) {
+            let opponent = &mut state.opponents[i];
+
+            if opponent.emergency_turns > 0 {
+                Self::float_up(opponent);
+                continue;
+            }
+
+            opponent.pos = (opponent.pos + action.get_move())
+                .clamp(Vec2::new(0., 0.), Vec2::new(9999., 9999.));
+
+            if action.light && opponent.bat >= LIGHT_COST {
+                opponent.bat -= LIGHT_COST;
+
+                let opponent_pos = opponent.pos;
+                let aggression: f32 = self
+                    .tracker
+                    .monsters
+                    .iter()
+                    .filter(|m| (m.pos - opponent_pos).len() < POWERED_LIGHT_RADIUS)
+                    .flat_map(|m| {
+                        state.drones[..state.drone_count]
+                            .iter()
+                            .filter(|d| d.emergency_turns == 0 && !d.missing)
+                            .map(|d| {
+                                (1. - (m.pos - d.pos).len() / self.params.danger_radius).max(0.)
+                            })
+                    })
+                    .sum();
+
+                state.score.aggression_score -=
+                    aggression * self.params.aggression_weight / iter as f32;
+            } else {
+                opponent.bat = (opponent.bat + 1).min(MAX_BATTERY);
+            }
+
+            let opponent = &mut state.opponents[i];
+
+            if opponent.pos.y < 400. {
+                let stolen = state.drones.iter().fold(0, |mask, d| mask | d.race_scans)
+                    & opponent.race_scans;
+                opponent.race_scans = 0;
+
+                for drone in &mut state.drones {
+                    let lost = drone.race_scans & stolen;
+                    drone.race_scans &= !lost;
+                    drone.urgent_scans_cost -= scans_cost(&state.fish_costs, lost);
+                }
+
+                state.score.race_score -= scans_cost(&state.fish_costs, stolen) as f32
+                    * self.params.race_weight
+                    / iter as f32;
+            }
+        }
+    }
+
+    pub(crate) fn simulate_all_against(
+        &mut self,
+        state: &mut GameState,
+        gene: &mut Gene,
+        opponent_gene: &Gene,
+    ) {
+        for (iter, (action, opponent_actions)) in gene.iter_mut().zip(opponent_gene).enumerate() {
+            self.simulate(state, action, iter + 1);
+            self.simulate_opponents(state, opponent_actions, iter + 1);
+        }
+
+        self.total_simulations += 1;
+
+        if state.any_caught() {
+            self.dead_simulations += 1;
+        }
+    }
+
+    pub(crate) fn simulate_all(&mut self, state: &mut GameState, gene: &mut Gene) {
+        for (iter, action) in gene.iter_mut().enumerate() {
+            self.simulate(state, action, iter + 1);
+        }
+
+        self.total_simulations += 1;
+
+        if state.any_caught() {
+            self.dead_simulations += 1;
+        }
+    }
+
+    /// Resumes from the last parent checkpoint taken before step `from`, and
+    /// saves a new checkpoint every `checkpoint_interval` steps.
+    pub(crate) fn simulate_cached(
+        &mut self,
+        state_proto: &GameState,
+        gene: &mut Gene,
+        parent: &[StateHandle],
+        from: usize,
+        checkpoints: &mut Vec<StateHandle>,
+    ) -> GameState {
+        let interval = self.params.checkpoint_interval;
+        let reused = from.checked_div(interval).unwrap_or(0).min(parent.len());
+
+        for &handle in &parent[..reused] {
+            self.states.retain(handle);
+            checkpoints.push(handle);
+        }
+
+        let mut state = match checkpoints.last() {
+            Some(&handle) => self.states.get(handle).clone(),
+            None => state_proto.clone(),
+        };
+
+        let start = reused * interval;
+        for (iter, actions) in gene.iter_mut().enumerate().skip(start) {
+            self.simulate(&mut state, actions, iter + 1);
+
+            if interval > 0 && (iter + 1) % interval == 0 && iter + 1 < GENE_SIZE {
+                checkpoints.push(self.states.insert(&state));
+            }
+        }
+
+        self.total_simulations += 1;
+        self.reused_steps += start as i32;
+
+        if state.any_caught() {
+            self.dead_simulations += 1;
+        }
+
+        state
+    }
+}
@@ -1,49 +1,212 @@
+use std::{
+    fmt::Display,
+    time::{Duration, Instant},
+};
+
 use super::*;
 
+#[derive(Clone, Copy, Debug)]
+pub struct DroneCommand {
+    pub target: Vec2,
+    pub light: bool,
+    pub wait: bool,
+}
+
+impl Display for DroneCommand {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let (x, y) = (self.target.x as usize, self.target.y as usize);
+        let light = if self.light { 1 } else { 0 };
+
+        if self.wait {
+            write!(f, "WAIT {light}")
+        } else {
+            write!(f, "MOVE {x} {y} {light}")
+        }
+    }
+}
+
+/// Time spent in each stage of the last decision.
+#[derive(Clone, Copy, Default, Debug)]
+pub struct StageTimes {
+    pub tracker: Duration,
+    pub bounds: Duration,
+    /// Exploration, score and meta strategy maps, the fish allocation and
+    /// the battery plan.
+    pub maps: Duration,
+    pub search: Duration,
+}
+
 pub struct Strategy {
     pub bounds_detector: BoundsDetector,
     tracker: Tracker,
     pub exploration_map: ExplorationMap,
     pub score_map: ScoreMap,
-    pub pathfinding: Pathfinding,
+    pub allocation: TaskAllocation,
+    /// `score_map` biased by `allocation`, by plan slot.
+    pub drone_score_maps: Vec<ScoreMap>,
+    pub battery: BatteryPlan,
+    pub planner: Box<dyn Planner>,
+    /// Book lines replayed until the first turn they are unsafe.
+    opening: Option<Opening>,
+    params: StrategyParams,
     pub meta_strategy: MetaStrategy,
+    /// Our drone ids by plan slot, taken from the first turn.
+    drone_ids: Vec<i32>,
+    /// Statistics of the last search.
+    pub report: SearchReport,
+    /// Statistics of every search of this game.
+    pub game_report: GameReport,
+    pub times: StageTimes,
 }
 
 impl Strategy {
     pub fn new() -> Self {
+        Self::with_params(BAKED_PARAMS)
+    }
+
+    pub fn with_params(params: StrategyParams) -> Self {
         Strategy {
             bounds_detector: BoundsDetector::new(),
             tracker: Tracker::new(),
             exploration_map: ExplorationMap::new(),
             score_map: ScoreMap::new(),
-            pathfinding: Pathfinding::new(),
+            allocation: TaskAllocation::new(),
+            drone_score_maps: Vec::new(),
+            battery: BatteryPlan::default(),
+            planner: new_planner(params),
+            params,
             meta_strategy: MetaStrategy::new(),
+            opening: None,
+            drone_ids: Vec::new(),
+            report: SearchReport::default(),
+            game_report: GameReport::default(),
+            times: StageTimes::default(),
         }
     }
 }
 
 impl Strategy {
-    pub fn play(&mut self, world: &World) {
+    pub fn decide(&mut self, world: &World) -> Vec<DroneCommand> {
+        let start = Instant::now();
+
+        if self.drone_ids.is_empty() {
+            self.drone_ids = world.me.drones.keys().take(MAX_DRONES).copied().collect();
+            if world.me.drones.len() > MAX_DRONES {
+                eprintln!(
+                    "warning: {} drones, only the first {MAX_DRONES} are planned",
+                    world.me.drones.len()
+                );
+            }
+            if self.params.opening_book {
+                self.opening = Opening::find(world, &self.drone_ids);
+            }
+        }
+
         self.tracker.update(world);
+        self.times.tracker = start.elapsed();
+
         self.bounds_detector.update(world);
-        self.exploration_map.update(world);
+        self.times.bounds = start.elapsed() - self.times.tracker;
+
+        self.exploration_map.update(world, &self.params);
         self.meta_strategy.update(world);
         self.score_map
             .update(world, &self.bounds_detector, &self.meta_strategy);
 
-        let actions =
-            self.pathfinding
-                .search(world, &self.tracker, &self.exploration_map, &self.score_map);
+        self.allocation.update(
+            world,
+            &self.bounds_detector,
+            &self.meta_strategy,
+            &self.drone_ids,
+        );
+        self.drone_score_maps
+            .resize_with(self.drone_ids.len(), ScoreMap::new);
+        for (drone_idx, map) in self.drone_score_maps.iter_mut().enumerate() {
+            let (allocation, bias) = (&self.allocation, self.params.allocation_bias);
+            map.update_weighted(world, &self.bounds_detector, &self.meta_strategy, |id| {
+                allocation.weight(id, drone_idx, bias)
+            });
+        }
+        self.battery
+            .update(world, &self.drone_score_maps, &self.drone_ids);
+        self.times.maps = start.elapsed() - self.times.tracker - self.times.bounds;
+
+        let ctx = PlanningContext {
+            world,
+            tracker: &self.tracker,
+            bounds_detector: &self.bounds_detector,
+            exploration_map: &self.exploration_map,
+            score_map: &self.score_map,
+            drone_score_maps: &self.drone_score_maps,
+            battery: &self.battery,
+            drone_ids: &self.drone_ids,
+        };
+        let budget =
+            Duration::from_millis(self.params.search_time_ms).saturating_sub(start.elapsed());
 
-        for (i, drone) in world.me.drones.values().enumerate() {
-            let action = actions[i];
+        let search_start = Instant::now();
+        let book_plan = self
+            .opening
+            .as_ref()
+            .and_then(|opening| opening.plan(&ctx, &self.params, world.iter as usize));
+        if book_plan.is_none() {
+            self.opening = None;
+        }
 
-            let pos = drone.pos + action.get_move();
-            let (x, y) = (pos.x as usize, pos.y as usize);
+        let plan = if let Some(plan) = book_plan {
+            plan
+        } else if budget < Duration::from_millis(self.params.fallback_budget_ms) {
+            fallback_plan(&ctx, &self.params)
+        } else {
+            self.planner.plan(&ctx, budget)
+        };
+        self.times.search = search_start.elapsed();
+        let actions = plan.actions;
+        self.report = plan.report;
+        self.game_report.add(&self.report);
 
-            let light = if action.get_light() { 1 } else { 0 };
+        world
+            .me
+            .drones
+            .iter()
+            .map(
+                |(id, drone)| match self.drone_ids.iter().position(|i| i == id) {
+                    Some(i) => DroneCommand {
+                        target: drone.pos + actions[i].get_move(),
+                        light: actions[i].get_light(),
+                        wait: actions[i].is_wait(),
+                    },
+                    None => DroneCommand {
+                        target: drone.pos,
+                        light: false,
+                        wait: true,
+                    },
+                },
+            )
+            .collect()
+    }
+
+    /// Planning context of the last decision, for offline tools.
+    pub fn context<'a>(&'a self, world: &'a World) -> PlanningContext<'a> {
+        PlanningContext {
+            world,
+            tracker: &self.tracker,
+            bounds_detector: &self.bounds_detector,
+            exploration_map: &self.exploration_map,
+            score_map: &self.score_map,
+            drone_score_maps: &self.drone_score_maps,
+            battery: &self.battery,
+            drone_ids: &self.drone_ids,
+        }
+    }
+
+    pub fn play(&mut self, world: &World) {
+        let commands = self.decide(world);
+        eprintln!("{}", self.report);
+        eprintln!("{}", self.game_report);
 
-            println!("MOVE {x} {y} {light}");
+        for command in commands {
+            println!("{command}");
         }
     }
 }
@@ -0,0 +1,89 @@
+use super::*;
+
+const SURFACE: f32 = 500.;
+
+/// Combo only completed by the scans of several of our drones. Its bonus is
+/// doubled for the player who saves all of it first.
+#[derive(Clone, Copy, Debug)]
+pub struct ComboGoal {
+    /// Plan slots of the drones that have to surface.
+    pub drones: u8,
+    /// Bonus of saving the combo first.
+    pub bonus: i32,
+    /// Last step to save the combo before the opponent can, `GENE_SIZE`
+    /// when the opponent can't complete it with its carried scans.
+    pub deadline: usize,
+}
+
+fn turns_to_surface(drone: &Drone) -> usize {
+    ((drone.pos.y - SURFACE) / DRONE_SPEED).ceil().max(0.) as usize
+}
+
+/// Fish ids of every combo with its bonus: all four of a type, all three
+/// of a colour.
+fn combos(world: &World) -> Vec<(Vec<i32>, i32)> {
+    let fish = world.creatures.values().filter(|c| c.typ != -1);
+
+    let by_type = (0..3).map(|typ| {
+        let ids = fish.clone().filter(|c| c.typ == typ).map(|c| c.id);
+        (ids.collect(), 4)
+    });
+    let by_color = (0..4).map(|color| {
+        let ids = fish.clone().filter(|c| c.color == color).map(|c| c.id);
+        (ids.collect(), 3)
+    });
+
+    by_type.chain(by_color).collect()
+}
+
+/// Turns until the opponent can save every fish of the combo with its
+/// carried scans, if it can.
+fn opponent_deadline(world: &World, ids: &[i32]) -> Option<usize> {
+    ids.iter()
+        .filter(|id| !world.opponent.scans.contains(id))
+        .map(|id| {
+            world
+                .opponent
+                .drones
+                .values()
+                .filter(|d| d.emergency == 0 && d.scans.contains(id))
+                .map(turns_to_surface)
+                .min()
+        })
+        .try_fold(0, |deadline, turns| Some(deadline.max(turns?)))
+}
+
+/// Combos whose missing fish are spread over several of our drones, with
+/// the drones that have to save them together and when. Each missing fish
+/// is assigned to its shallowest carrier.
+pub fn combo_goals(world: &World, drone_ids: &[i32]) -> Vec<ComboGoal> {
+    let drones = drone_ids
+        .iter()
+        .take(MAX_DRONES)
+        .map(|id| world.me.drones.get(id).filter(|d| d.emergency == 0))
+        .collect::<Vec<_>>();
+
+    combos(world)
+        .into_iter()
+        .filter(|(ids, _)| !ids.iter().all(|id| world.opponent.scans.contains(id)))
+        .filter_map(|(ids, bonus)| {
+            let mut mask = 0u8;
+
+            for id in ids.iter().filter(|id| !world.me.scans.contains(id)) {
+                let carrier = drones
+                    .iter()
+                    .enumerate()
+                    .filter_map(|(slot, d)| d.filter(|d| d.scans.contains(id)).map(|d| (slot, d)))
+                    .min_by_key(|(_, d)| turns_to_surface(d))?;
+
+                mask |= 1 << carrier.0;
+            }
+
+            (mask.count_ones() > 1).then(|| ComboGoal {
+                drones: mask,
+                bonus: 2 * bonus,
+                deadline: opponent_deadline(world, &ids).map_or(GENE_SIZE, |turns| turns.max(1)),
+            })
+        })
+        .collect()
+}
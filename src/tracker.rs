@@ -2,6 +2,10 @@ use std::collections::HashMap;
 
 use super::*;
 
+/// Distance to a monster drones keep, wider than the 500 kill radius to
+/// cover the moves of the monster over the next turns.
+const DANGER_RADIUS: f32 = 1200.;
+
 #[derive(Clone)]
 pub struct Monster {
     id: i32,
@@ -10,6 +14,13 @@ pub struct Monster {
     target: Option<Vec2>,
 }
 
+impl Monster {
+    /// Position in `turns` turns if the monster keeps its current velocity.
+    pub fn pos_at(&self, turns: usize) -> Vec2 {
+        self.pos + self.vel * turns as f32
+    }
+}
+
 pub struct Tracker {
     pub monsters: Vec<Monster>,
     drone_bat: HashMap<i32, i32>,
@@ -122,24 +133,21 @@ impl Tracker {
         }
     }
 
+    /// Whether `pos` is within `DANGER_RADIUS` of a monster's current
+    /// position. Extrapolated positions are mostly noise a few turns ahead,
+    /// so the radius takes the monster moves into account instead. The one
+    /// danger check of the simulation, the plan repair and the opening book.
+    pub fn is_dangerous(&self, pos: Vec2) -> bool {
+        self.monsters
+            .iter()
+            .any(|m| (m.pos - pos).len() < DANGER_RADIUS)
+    }
+
     pub fn update(&mut self, world: &World) {
         self.update_monster_velocities(world);
         self.update_monster_positions();
         self.update_visible(world);
         self.update_monsters_targets(world);
         self.update_bat(world);
-
-        eprintln!("Monster tracking:");
-        for m in &self.monsters {
-            eprintln!(
-                "id:{}, pos: {}:{} vel: {}:{} target: {}",
-                m.id,
-                m.pos.x as i32,
-                m.pos.y as i32,
-                m.vel.x as i32,
-                m.vel.y as i32,
-                if m.target.is_some() { "yes" } else { "No" }
-            );
-        }
     }
 }